@@ -0,0 +1,84 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use snake::{BoundaryMode, Game, Level, RandomNumberGenerator, Snake};
+
+const WIDTH: usize = 100;
+const HEIGHT: usize = 100;
+const TICKS: usize = 10;
+
+struct ZeroGenerator;
+
+impl RandomNumberGenerator for ZeroGenerator {
+    fn from_seed(_: u64) -> Self {
+        ZeroGenerator
+    }
+
+    fn next(&mut self) -> u32 {
+        0
+    }
+
+    fn state(&self) -> u128 {
+        0
+    }
+
+    fn from_state(_: u128) -> Self {
+        ZeroGenerator
+    }
+}
+
+/// Lays a snake of `length` segments out in rows from the bottom of the board, leaving every
+/// other row empty so the segments only touch their neighbours. The head points up into the
+/// empty top of the board.
+fn level_with_snake(length: usize) -> String {
+    let mut rows = vec![vec![b'.'; WIDTH]; HEIGHT];
+    let mut remaining = length - 1;
+    let mut y = HEIGHT - 1;
+    let mut x = 0;
+    let mut step: i32 = 1;
+
+    while remaining > 0 {
+        rows[y][x] = b'O';
+        remaining -= 1;
+
+        let next = x as i32 + step;
+        if (0..WIDTH as i32).contains(&next) {
+            x = next as usize;
+        } else if rows[y - 1][x] == b'.' && y % 2 == 1 {
+            y -= 1;
+        } else {
+            y -= 1;
+            step = -step;
+        }
+    }
+
+    rows[y][x] = b'^';
+    rows.iter()
+        .map(|r| String::from_utf8(r.clone()).unwrap() + "\n")
+        .collect()
+}
+
+fn advance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("advance_100x100");
+
+    for length in [4, 100, 1_000, 4_000] {
+        let text = level_with_snake(length);
+        let level = Level::parse(&text).unwrap();
+        assert_eq!(length, level.snake_length());
+
+        group.bench_with_input(BenchmarkId::from_parameter(length), &level, |b, level| {
+            b.iter_batched_ref(
+                || Box::new(Game::<{ WIDTH * HEIGHT }, ZeroGenerator>::from_level(level, BoundaryMode::Wrap)),
+                |game| {
+                    for _ in 0..TICKS {
+                        game.advance();
+                    }
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, advance);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use snake::{Action, Environment, GameBuilder, Observation, Rewards};
+
+const STEPS: u64 = 1_000;
+
+/// Steps through episodes on a 20x20 board, turning every few steps so the snake doesn't only
+/// go in circles
+fn step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("environment_step_20x20");
+    group.throughput(Throughput::Elements(STEPS));
+
+    let observations = [
+        ("grid", Observation::Grid),
+        ("window", Observation::Window { radius: 5 }),
+        ("features", Observation::Features),
+    ];
+    for (name, observation) in observations {
+        let mut env = Environment::new(GameBuilder::new(20, 20), observation, Rewards::default()).unwrap();
+        let mut seed = 0;
+
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                for i in 0..STEPS {
+                    let action = Action::ALL[(i % 7 / 5) as usize];
+                    if env.step(action).2 {
+                        seed += 1;
+                        env.reset(seed);
+                    }
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, step);
+criterion_main!(benches);
@@ -0,0 +1,329 @@
+//! One place to set up a game instead of picking the right constructor.
+
+use core::fmt;
+
+use crate::{
+    Board, BoundaryMode, Direction, FruitPolicy, GenericGame, GenericMultiGame, GridBoard, Level, Location,
+    RandomNumberGenerator, Square, Storage, DEFAULT_SEED, MAX_PLAYERS,
+};
+
+/// Why a `GameBuilder` could not create a game
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum BuildError {
+    /// The board has more squares than the storage can hold
+    DoesNotFit { width: usize, height: usize },
+    /// The initial length is 0
+    NoSnake,
+    /// A segment of the snake would lie outside the board
+    OffBoard { at: Location },
+    /// A segment of the snake would lie on a wall or on another snake
+    Obstacle { at: Location },
+    /// A multi-snake game needs between 1 and `MAX_PLAYERS` players
+    Players { count: usize },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::DoesNotFit { width, height } => {
+                write!(f, "{}x{} board does not fit in the storage", width, height)
+            }
+            BuildError::NoSnake => write!(f, "snake must have at least one segment"),
+            BuildError::OffBoard { at } => write!(f, "snake does not fit on the board at {},{}", at.x, at.y),
+            BuildError::Obstacle { at } => write!(f, "snake overlaps an obstacle at {},{}", at.x, at.y),
+            BuildError::Players { count } => {
+                write!(f, "{} players given, there can be from 1 to {}", count, MAX_PLAYERS)
+            }
+        }
+    }
+}
+
+/// Sets up a game, everything that isn't set is the same as in `Game::new`
+///
+/// ```rust
+/// use self::snake::{BoundaryMode, Direction, Fixed, GameBuilder, Location, Pcg32, Snake};
+/// let game = GameBuilder::new(8, 6)
+///     .seed(7)
+///     .boundary_mode(BoundaryMode::Solid)
+///     .initial_length(4)
+///     .direction(Direction::Up)
+///     .start(Location::new(2, 1))
+///     .build::<Fixed<{ 8 * 6 }>, Pcg32>();
+///
+/// assert_eq!(4, game.length());
+/// assert_eq!(Location::new(2, 1), game.head());
+/// assert_eq!(Direction::Up, game.direction());
+/// ```
+#[derive(Clone, Debug)]
+pub struct GameBuilder<'a> {
+    width: usize,
+    height: usize,
+    seed: u64,
+    boundary_mode: BoundaryMode,
+    level: Option<Level<'a>>,
+    walls: &'a [Location],
+    initial_length: usize,
+    direction: Direction,
+    start: Option<Location>,
+    fruit_policy: FruitPolicy,
+}
+
+impl<'a> GameBuilder<'a> {
+    pub fn new(width: usize, height: usize) -> Self {
+        GameBuilder {
+            width,
+            height,
+            seed: DEFAULT_SEED,
+            boundary_mode: BoundaryMode::default(),
+            level: None,
+            walls: &[],
+            initial_length: 2,
+            direction: Direction::Right,
+            start: None,
+            fruit_policy: FruitPolicy::new(),
+        }
+    }
+
+    /// Takes the size, walls, snake and fruit spawn points from `level`
+    ///
+    /// The snake of the level is replaced when `start` is called, the initial length and
+    /// direction set on the builder are used for it then.
+    pub fn level(mut self, level: &Level<'a>) -> Self {
+        self.width = level.width();
+        self.height = level.height();
+        self.level = Some(level.clone());
+        self
+    }
+
+    /// Seed for the generator created by `build`
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn boundary_mode(mut self, boundary_mode: BoundaryMode) -> Self {
+        self.boundary_mode = boundary_mode;
+        self
+    }
+
+    /// Walls added on top of the ones from the level
+    pub fn walls(mut self, walls: &'a [Location]) -> Self {
+        self.walls = walls;
+        self
+    }
+
+    pub fn initial_length(mut self, length: usize) -> Self {
+        self.initial_length = length;
+        self
+    }
+
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Where the head starts, the body trails straight behind it, by default the head is in
+    /// the middle of the board
+    pub fn start(mut self, head: Location) -> Self {
+        self.start = Some(head);
+        self
+    }
+
+    /// Extra fruits besides the apple, only used for a single snake
+    pub fn fruit_policy(mut self, policy: FruitPolicy) -> Self {
+        self.fruit_policy = policy;
+        self
+    }
+
+    /// Applies `configure` to the builder, handy when the settings depend on something
+    pub fn configure(self, configure: impl FnOnce(Self) -> Self) -> Self {
+        configure(self)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Creates the game with a generator made from the seed
+    ///
+    /// Panics when `try_build` would fail.
+    pub fn build<S: Storage, R: RandomNumberGenerator>(&self) -> GenericGame<S, R> {
+        self.build_with_rng(R::from_seed(self.seed))
+    }
+
+    /// Like `build` but uses `rng` instead of seeding a new generator
+    pub fn build_with_rng<S: Storage, R: RandomNumberGenerator>(&self, rng: R) -> GenericGame<S, R> {
+        self.try_build_with_rng(rng).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates the game with a generator made from the seed, checking that the board fits in
+    /// `S` and that the snake lies on the board clear of walls
+    ///
+    /// ```rust
+    /// use self::snake::{BuildError, Fixed, GameBuilder, Location, Pcg32};
+    /// let walls = [Location::new(1, 0)];
+    /// let result = GameBuilder::new(4, 1)
+    ///     .walls(&walls)
+    ///     .start(Location::new(2, 0))
+    ///     .try_build::<Fixed<4>, Pcg32>();
+    ///
+    /// assert_eq!(Some(BuildError::Obstacle { at: Location::new(1, 0) }), result.err());
+    /// ```
+    pub fn try_build<S: Storage, R: RandomNumberGenerator>(&self) -> Result<GenericGame<S, R>, BuildError> {
+        self.try_build_with_rng(R::from_seed(self.seed))
+    }
+
+    /// Like `try_build` but uses `rng` instead of seeding a new generator
+    pub fn try_build_with_rng<S: Storage, R: RandomNumberGenerator>(
+        &self,
+        rng: R,
+    ) -> Result<GenericGame<S, R>, BuildError> {
+        let (width, height) = (self.width, self.height);
+        if !width.checked_mul(height).is_some_and(S::fits) {
+            return Err(BuildError::DoesNotFit { width, height });
+        }
+
+        let level_snake = self.level.as_ref().filter(|_| self.start.is_none());
+        let direction = level_snake.map_or(self.direction, Level::direction);
+        let mut game: GenericGame<S, R> = GenericGame::empty(width, height, self.boundary_mode, direction, rng);
+
+        game.fruit_policy = self.fruit_policy;
+        self.place_walls(&mut game.board)?;
+        for spawn in self.fruit_spawns() {
+            game.add_fruit_spawn(spawn);
+        }
+
+        match level_snake {
+            Some(level) => {
+                let length = level.snake_length();
+                for (i, location) in level.snake().enumerate() {
+                    if game.board.at(location) == Square::Wall {
+                        return Err(BuildError::Obstacle { at: location });
+                    }
+                    game.snake[length - 1 - i] = location;
+                    *game.board.at_mut(&location) = Square::Snake;
+                }
+                game.snake_size = length;
+            }
+            None => {
+                if self.initial_length == 0 {
+                    return Err(BuildError::NoSnake);
+                }
+
+                let head = self.start.unwrap_or_else(|| Location::new(width / 2, height / 2));
+                let mut segment = (1..self.initial_length).fold(head, |l, _| l.move_in(direction.opposite()));
+                for _ in 0..self.initial_length {
+                    if !segment.is_within(width, height) {
+                        return Err(BuildError::OffBoard { at: segment });
+                    }
+                    if game.board.at(segment) == Square::Wall {
+                        return Err(BuildError::Obstacle { at: segment });
+                    }
+                    game.push_head(segment);
+                    segment = segment.move_in(direction);
+                }
+            }
+        }
+
+        game.remember_start();
+        game.status = game.respawn_fruit();
+        Ok(game)
+    }
+
+    /// Creates a game of `PLAYERS` snakes with a generator made from the seed
+    ///
+    /// Panics when `try_build_multi` would fail.
+    pub fn build_multi<S: Storage, R: RandomNumberGenerator, const PLAYERS: usize>(
+        &self,
+    ) -> GenericMultiGame<S, R, PLAYERS> {
+        self.try_build_multi_with_rng(R::from_seed(self.seed)).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a game of `PLAYERS` snakes, see `GenericMultiGame` for where they start
+    ///
+    /// The size, seed, boundary mode, walls, fruit spawn points and initial length are used
+    /// like for a single snake, the snake of a level, the start, direction and fruit policy
+    /// are not.
+    ///
+    /// ```rust
+    /// use self::snake::{BuildError, Fixed, GameBuilder, Location, Pcg32};
+    /// let result = GameBuilder::new(4, 1).try_build_multi::<Fixed<4>, Pcg32, 2>();
+    ///
+    /// assert_eq!(Some(BuildError::Obstacle { at: Location::new(2, 0) }), result.err());
+    /// ```
+    pub fn try_build_multi<S: Storage, R: RandomNumberGenerator, const PLAYERS: usize>(
+        &self,
+    ) -> Result<GenericMultiGame<S, R, PLAYERS>, BuildError> {
+        self.try_build_multi_with_rng(R::from_seed(self.seed))
+    }
+
+    /// Like `try_build_multi` but uses `rng` instead of seeding a new generator
+    pub fn try_build_multi_with_rng<S: Storage, R: RandomNumberGenerator, const PLAYERS: usize>(
+        &self,
+        rng: R,
+    ) -> Result<GenericMultiGame<S, R, PLAYERS>, BuildError> {
+        let (width, height) = (self.width, self.height);
+        if !width.checked_mul(height).is_some_and(S::fits) {
+            return Err(BuildError::DoesNotFit { width, height });
+        }
+        if PLAYERS == 0 || PLAYERS > MAX_PLAYERS {
+            return Err(BuildError::Players { count: PLAYERS });
+        }
+        if self.initial_length == 0 {
+            return Err(BuildError::NoSnake);
+        }
+
+        let mut game: GenericMultiGame<S, R, PLAYERS> =
+            GenericMultiGame::empty(width, height, self.boundary_mode, self.initial_length, rng);
+
+        self.place_walls(&mut game.board)?;
+        for spawn in self.fruit_spawns() {
+            game.add_fruit_spawn(spawn);
+        }
+
+        game.place_snakes()?;
+        game.place_first_fruit();
+        Ok(game)
+    }
+
+    fn place_walls<S: Storage>(&self, board: &mut GridBoard<S>) -> Result<(), BuildError> {
+        let level_walls = self.level.iter().flat_map(Level::walls);
+        for wall in level_walls.chain(self.walls.iter().copied()) {
+            if !wall.is_within(self.width, self.height) {
+                return Err(BuildError::OffBoard { at: wall });
+            }
+            *board.at_mut(&wall) = Square::Wall;
+        }
+        Ok(())
+    }
+
+    fn fruit_spawns(&self) -> impl Iterator<Item = Location> + '_ {
+        self.level.iter().flat_map(Level::fruit_spawns)
+    }
+}
+
+/// Creates a `Game` computing its `SIZE` from the width and height, the settings can be changed
+/// with a closure taking and returning a `GameBuilder`
+///
+/// ```rust
+/// use self::snake::{create_game_instance, BoundaryMode, Pcg32, Snake};
+/// let game = create_game_instance!(22, 16, Pcg32);
+/// let solid = create_game_instance!(22, 16, Pcg32, |b| b.seed(3).boundary_mode(BoundaryMode::Solid));
+///
+/// assert_eq!(22, game.board().width());
+/// ```
+#[macro_export]
+macro_rules! create_game_instance {
+    ($width:expr, $height:expr, $rng:ty) => {
+        $crate::create_game_instance!($width, $height, $rng, |builder| builder)
+    };
+    ($width:expr, $height:expr, $rng:ty, $configure:expr) => {
+        $crate::GameBuilder::new($width, $height)
+            .configure($configure)
+            .build::<$crate::Fixed<{ $width * $height }>, $rng>()
+    };
+}
@@ -0,0 +1,324 @@
+//! Players built into the engine, for demos and for trying out the game.
+//!
+//! A controller looks at a game through `Snake` and tells which way to turn before the next
+//! tick. It expects to be the only one steering, turns queued by someone else are not taken
+//! into account.
+
+use crate::{Board, Direction, GameStatus, Location, Snake, Square, Storage};
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+/// Decides where the snake goes, asked once before every tick
+pub trait Controller {
+    /// Direction to turn to, `None` keeps the snake going the way it goes
+    fn next_direction(&mut self, game: &dyn Snake) -> Option<Direction>;
+
+    /// Asks for a direction and turns the snake
+    fn steer(&mut self, game: &mut dyn Snake) {
+        if let Some(direction) = self.next_direction(game) {
+            game.set_direction(direction);
+        }
+    }
+}
+
+/// Where the head ends up after moving from `from` in `direction`, `None` past a solid edge
+fn step(game: &dyn Snake, from: Location, direction: Direction) -> Option<Location> {
+    let board = game.board();
+    game.boundary_mode().apply(from.move_in(direction), board.width(), board.height())
+}
+
+/// Turn that leads from the head to `to`
+fn turn_towards(game: &dyn Snake, to: Location) -> Option<Direction> {
+    let direction = DIRECTIONS
+        .into_iter()
+        .find(|d| step(game, game.head(), *d) == Some(to))?;
+    (direction != game.direction()).then_some(direction)
+}
+
+/// Goes for the apple along the shortest path, but only when the snake could still reach its
+/// tail after eating it. Otherwise it follows its tail the long way round until the apple is
+/// safe to take.
+///
+/// Needs room for a copy of the snake and the search, `S` has to fit the board of the game.
+///
+/// ```rust
+/// use self::snake::{Controller, Fixed, Game, GameStatus, Greedy, Pcg32, Snake};
+/// let mut game = Game::<{ 10 * 10 }, Pcg32>::new(10, 10);
+/// let mut autopilot = Greedy::<Fixed<{ 10 * 10 }>>::new(10, 10);
+///
+/// while game.score() < 10 {
+///     autopilot.steer(&mut game);
+///     assert_eq!(GameStatus::InProgress, game.advance());
+/// }
+/// ```
+pub struct Greedy<S: Storage> {
+    width: usize,
+    height: usize,
+    /// Breadth first search from the head, `u32::MAX` for squares not reached
+    distance: S::Array<u32>,
+    parent: S::Array<usize>,
+    queue: S::Array<usize>,
+    /// Squares the snake would occupy after moving, without its tail
+    occupied: S::Array<bool>,
+    /// Segments of the snake from the tail to the head
+    body: S::Array<Location>,
+    /// Squares the head would go through, the first one is next to the head
+    path: S::Array<Location>,
+}
+
+impl<S: Storage> Greedy<S> {
+    pub fn new(width: usize, height: usize) -> Self {
+        let size = width * height;
+        Greedy {
+            width,
+            height,
+            distance: S::array(u32::MAX, size),
+            parent: S::array(0, size),
+            queue: S::array(0, size),
+            occupied: S::array(false, size),
+            body: S::array(Location::default(), size),
+            path: S::array(Location::default(), size),
+        }
+    }
+
+    fn index(&self, location: Location) -> usize {
+        location.y as usize * self.width + location.x as usize
+    }
+
+    /// Square `index` of the body followed by the path
+    fn trail(&self, length: usize, index: usize) -> Location {
+        match index.checked_sub(length) {
+            Some(i) => self.path[i],
+            None => self.body[index],
+        }
+    }
+
+    /// Breadth first search from `from` around walls and occupied squares, returns the number
+    /// of squares reached. `heading` can't be reversed on the first step.
+    fn search(&mut self, board: &dyn Board, game: &dyn Snake, from: Location, heading: Option<Direction>) -> usize {
+        self.distance.as_mut().fill(u32::MAX);
+        let start = self.index(from);
+        self.distance[start] = 0;
+        self.queue[0] = start;
+        let (mut first, mut last) = (0, 1);
+
+        while first < last {
+            let current = self.queue[first];
+            first += 1;
+            let location = Location::new(current % self.width, current / self.width);
+
+            for direction in DIRECTIONS {
+                if current == start && heading == Some(direction.opposite()) {
+                    continue;
+                }
+                let Some(next) = step(game, location, direction) else {
+                    continue;
+                };
+                let index = self.index(next);
+                let obstacle = matches!(board.at(next), Square::Wall | Square::Player(_)) || self.occupied[index];
+                if obstacle || self.distance[index] != u32::MAX {
+                    continue;
+                }
+                self.distance[index] = self.distance[current] + 1;
+                self.parent[index] = current;
+                self.queue[last] = index;
+                last += 1;
+            }
+        }
+
+        last
+    }
+
+    /// Marks the snake as it would be after the head went `steps` squares along the path, the
+    /// tail is left unmarked. Returns the tail and the head.
+    fn occupy(&mut self, length: usize, steps: usize, eats: bool) -> (Location, Location) {
+        let total = length + steps;
+        let tail = total - length - usize::from(eats);
+        self.occupied.as_mut().fill(false);
+        for i in tail + 1..total {
+            let index = self.index(self.trail(length, i));
+            self.occupied[index] = true;
+        }
+        (self.trail(length, tail), self.trail(length, total - 1))
+    }
+
+    /// How far the tail would be from the head after going `steps` squares along the path
+    fn tail_distance(&mut self, game: &dyn Snake, steps: usize, eats: bool) -> Option<u32> {
+        let (tail, head) = self.occupy(game.length(), steps, eats);
+        if tail == head {
+            return Some(0);
+        }
+        self.search(game.board(), game, head, None);
+        Some(self.distance[self.index(tail)]).filter(|d| *d != u32::MAX)
+    }
+
+    /// Shortest path to the apple if the tail can be reached after eating it
+    fn safe_path_to(&mut self, game: &dyn Snake, apple: Location) -> Option<Location> {
+        self.occupied.as_mut().fill(false);
+        for i in 0..game.length() {
+            let index = self.index(self.body[i]);
+            self.occupied[index] = true;
+        }
+        self.search(game.board(), game, game.head(), Some(game.direction()));
+
+        let mut index = self.index(apple);
+        let steps = match self.distance[index] {
+            0 | u32::MAX => return None,
+            distance => distance as usize,
+        };
+        for i in (0..steps).rev() {
+            self.path[i] = Location::new(index % self.width, index / self.width);
+            index = self.parent[index];
+        }
+
+        self.tail_distance(game, steps, true)?;
+        Some(self.path[0])
+    }
+
+    /// Free square next to the head that keeps the tail farthest away while still reachable,
+    /// or with the most room when the tail can't be reached from any of them
+    fn safest_step(&mut self, game: &dyn Snake, apple: Option<Location>) -> Option<Location> {
+        let board = game.board();
+        let mut best: Option<(Location, bool, usize)> = None;
+
+        for direction in DIRECTIONS.into_iter().filter(|d| *d != game.direction().opposite()) {
+            let Some(next) = step(game, game.head(), direction) else {
+                continue;
+            };
+            if !matches!(board.at(next), Square::Empty | Square::Fruit(_)) {
+                continue;
+            }
+
+            self.path[0] = next;
+            let score = match self.tail_distance(game, 1, Some(next) == apple) {
+                Some(distance) => (next, true, distance as usize),
+                None => (next, false, self.search(board, game, next, Some(direction))),
+            };
+            if best.is_none_or(|(_, reaches, room)| (score.1, score.2) > (reaches, room)) {
+                best = Some(score);
+            }
+        }
+
+        best.map(|(location, _, _)| location)
+    }
+}
+
+impl<S: Storage> Controller for Greedy<S> {
+    fn next_direction(&mut self, game: &dyn Snake) -> Option<Direction> {
+        let board = game.board();
+        if game.status() != GameStatus::InProgress || (board.width(), board.height()) != (self.width, self.height) {
+            return None;
+        }
+
+        for i in 0..game.length() {
+            self.body[i] = game.segment(i)?;
+        }
+
+        let next = match game.fruit().and_then(|apple| self.safe_path_to(game, apple)) {
+            Some(next) => next,
+            None => self.safest_step(game, game.fruit())?,
+        };
+        turn_towards(game, next)
+    }
+}
+
+/// Follows a cycle going through every square of the board, so it never runs into itself and
+/// wins every game on a board without walls
+///
+/// Such a cycle only exists when the width or the height is even. The snake is expected to lie
+/// along the cycle, in either direction, which a snake of 2 segments always does.
+///
+/// ```rust
+/// use self::snake::{Controller, Fixed, Game, GameStatus, Hamiltonian, Pcg32, Snake};
+/// let mut game = Game::<{ 4 * 3 }, Pcg32>::new(4, 3);
+/// let mut autopilot = Hamiltonian::<Fixed<{ 4 * 3 }>>::new(4, 3).unwrap();
+///
+/// while game.status() == GameStatus::InProgress {
+///     autopilot.steer(&mut game);
+///     game.advance();
+/// }
+/// assert_eq!(GameStatus::Won, game.status());
+/// assert!(Hamiltonian::<Fixed<{ 3 * 3 }>>::new(3, 3).is_none());
+/// ```
+pub struct Hamiltonian<S: Storage> {
+    width: usize,
+    height: usize,
+    /// Direction leading from every square to the next one on the cycle
+    next: S::Array<Direction>,
+}
+
+impl<S: Storage> Hamiltonian<S> {
+    /// `None` when both the width and the height are odd or either is smaller than 2
+    pub fn new(width: usize, height: usize) -> Option<Self> {
+        // The cycle is laid out on a board with an even number of rows, turned if necessary
+        let transposed = !height.is_multiple_of(2);
+        let (w, h) = if transposed { (height, width) } else { (width, height) };
+        if w < 2 || h < 2 || !h.is_multiple_of(2) {
+            return None;
+        }
+
+        let mut next = S::array(Direction::Up, width * height);
+        for y in 0..h {
+            for x in 0..w {
+                let direction = cycle_direction(x, y, w, h);
+                match transposed {
+                    false => next[y * width + x] = direction,
+                    true => next[x * width + y] = transpose(direction),
+                }
+            }
+        }
+
+        Some(Hamiltonian { width, height, next })
+    }
+
+    fn next_on_cycle(&self, location: Location) -> Direction {
+        self.next[location.y as usize * self.width + location.x as usize]
+    }
+}
+
+/// The top row goes right, the other rows except the first column go back and forth down the
+/// board and the first column leads back up
+fn cycle_direction(x: usize, y: usize, width: usize, height: usize) -> Direction {
+    match (x, y) {
+        (x, 0) if x < width - 1 => Direction::Right,
+        (_, 0) => Direction::Down,
+        (0, _) => Direction::Up,
+        (x, y) if !y.is_multiple_of(2) && (x > 1 || y == height - 1) => Direction::Left,
+        (_, y) if !y.is_multiple_of(2) => Direction::Down,
+        (x, _) if x < width - 1 => Direction::Right,
+        _ => Direction::Down,
+    }
+}
+
+fn transpose(direction: Direction) -> Direction {
+    match direction {
+        Direction::Up => Direction::Left,
+        Direction::Down => Direction::Right,
+        Direction::Left => Direction::Up,
+        Direction::Right => Direction::Down,
+    }
+}
+
+impl<S: Storage> Controller for Hamiltonian<S> {
+    fn next_direction(&mut self, game: &dyn Snake) -> Option<Direction> {
+        let board = game.board();
+        if game.status() != GameStatus::InProgress || (board.width(), board.height()) != (self.width, self.height) {
+            return None;
+        }
+
+        let head = game.head();
+        let forward = self.next_on_cycle(head);
+        let neck = game.length().checked_sub(2).and_then(|i| game.segment(i));
+        let direction = if neck == Some(head.move_in(forward)) {
+            // The snake goes round the cycle the other way
+            DIRECTIONS.into_iter().find(|d| {
+                let previous = head.move_in(*d);
+                previous.is_within(self.width, self.height) && self.next_on_cycle(previous) == d.opposite()
+            })?
+        } else {
+            forward
+        };
+
+        (direction != game.direction()).then_some(direction)
+    }
+}
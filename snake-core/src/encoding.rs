@@ -0,0 +1,237 @@
+//! Little helpers for the binary formats, integers are LEB128 varints unless stated otherwise
+
+use crate::{BoundaryMode, Direction, FruitKind, FruitPolicy, GameStatus, Square, MAX_EXTRA_FRUITS, MAX_PLAYERS};
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct BufferFull;
+
+pub struct Writer<'a> {
+    buffer: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Writer {
+            buffer,
+            position: 0,
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) -> Result<(), BufferFull> {
+        let end = self.position + bytes.len();
+        self.buffer
+            .get_mut(self.position..end)
+            .ok_or(BufferFull)?
+            .copy_from_slice(bytes);
+        self.position = end;
+        Ok(())
+    }
+
+    pub fn u8(&mut self, value: u8) -> Result<(), BufferFull> {
+        self.bytes(&[value])
+    }
+
+    pub fn u32_le(&mut self, value: u32) -> Result<(), BufferFull> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn u64_le(&mut self, value: u64) -> Result<(), BufferFull> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn varint(&mut self, mut value: u64) -> Result<(), BufferFull> {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                return self.u8(byte);
+            }
+            self.u8(byte | 0x80)?;
+        }
+    }
+}
+
+/// Reading past the end or overlong varints give `None`
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn bytes(&mut self, count: usize) -> Option<&'a [u8]> {
+        if count > self.bytes.len() {
+            return None;
+        }
+        let (result, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Some(result)
+    }
+
+    pub fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    pub fn u32_le(&mut self) -> Option<u32> {
+        self.bytes(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub fn u64_le(&mut self) -> Option<u64> {
+        let mut result = [0; 8];
+        result.copy_from_slice(self.bytes(8)?);
+        Some(u64::from_le_bytes(result))
+    }
+
+    pub fn varint(&mut self) -> Option<u64> {
+        let mut result = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            result |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+        }
+        None
+    }
+}
+
+/// FNV-1a, good enough to tell two game states apart
+pub fn checksum(bytes: impl Iterator<Item = u8>) -> u32 {
+    bytes.fold(0x811c_9dc5, |hash, byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193))
+}
+
+pub fn direction_to_u8(direction: Direction) -> u8 {
+    match direction {
+        Direction::Up => 0,
+        Direction::Down => 1,
+        Direction::Left => 2,
+        Direction::Right => 3,
+    }
+}
+
+pub fn direction_from_u8(value: u8) -> Option<Direction> {
+    match value {
+        0 => Some(Direction::Up),
+        1 => Some(Direction::Down),
+        2 => Some(Direction::Left),
+        3 => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+pub fn boundary_mode_to_u8(boundary_mode: BoundaryMode) -> u8 {
+    match boundary_mode {
+        BoundaryMode::Wrap => 0,
+        BoundaryMode::Solid => 1,
+        BoundaryMode::WrapHorizontally => 2,
+        BoundaryMode::WrapVertically => 3,
+    }
+}
+
+pub fn boundary_mode_from_u8(value: u8) -> Option<BoundaryMode> {
+    match value {
+        0 => Some(BoundaryMode::Wrap),
+        1 => Some(BoundaryMode::Solid),
+        2 => Some(BoundaryMode::WrapHorizontally),
+        3 => Some(BoundaryMode::WrapVertically),
+        _ => None,
+    }
+}
+
+pub fn status_to_u8(status: GameStatus) -> u8 {
+    match status {
+        GameStatus::InProgress => 0,
+        GameStatus::Lost => 1,
+        GameStatus::Won => 2,
+        GameStatus::Paused => 3,
+    }
+}
+
+pub fn status_from_u8(value: u8) -> Option<GameStatus> {
+    match value {
+        0 => Some(GameStatus::InProgress),
+        1 => Some(GameStatus::Lost),
+        2 => Some(GameStatus::Won),
+        3 => Some(GameStatus::Paused),
+        _ => None,
+    }
+}
+
+pub fn square_to_u8(square: Square) -> u8 {
+    match square {
+        Square::Empty => 0,
+        Square::Snake => 1,
+        Square::Fruit(FruitKind::Apple) => 2,
+        Square::Fruit(kind) => 32 + fruit_kind_to_u8(kind),
+        Square::Wall => 3,
+        Square::Player(n) => 4 + n,
+    }
+}
+
+pub fn square_from_u8(value: u8) -> Option<Square> {
+    match value {
+        0 => Some(Square::Empty),
+        1 => Some(Square::Snake),
+        2 => Some(Square::Fruit(FruitKind::Apple)),
+        33..=35 => fruit_kind_from_u8(value - 32).map(Square::Fruit),
+        3 => Some(Square::Wall),
+        4.. if usize::from(value - 4) < MAX_PLAYERS => Some(Square::Player(value - 4)),
+        _ => None,
+    }
+}
+
+pub fn fruit_kind_to_u8(kind: FruitKind) -> u8 {
+    match kind {
+        FruitKind::Apple => 0,
+        FruitKind::Bonus => 1,
+        FruitKind::Feast => 2,
+        FruitKind::Shrink => 3,
+    }
+}
+
+pub fn fruit_kind_from_u8(value: u8) -> Option<FruitKind> {
+    match value {
+        0 => Some(FruitKind::Apple),
+        1 => Some(FruitKind::Bonus),
+        2 => Some(FruitKind::Feast),
+        3 => Some(FruitKind::Shrink),
+        _ => None,
+    }
+}
+
+/// Number of extra fruits, chance, lifetime plus one or 0 when there is none and the weights
+/// of the kinds
+pub fn write_fruit_policy(writer: &mut Writer, policy: &FruitPolicy) -> Result<(), BufferFull> {
+    writer.varint(policy.extra_fruits() as u64)?;
+    writer.varint(u64::from(policy.chance_percent()))?;
+    writer.varint(policy.lifetime_ticks().map_or(0, |ticks| u64::from(ticks) + 1))?;
+    for kind in FruitKind::ALL {
+        writer.varint(u64::from(policy.weight_of(kind)))?;
+    }
+    Ok(())
+}
+
+pub fn read_fruit_policy(reader: &mut Reader) -> Option<FruitPolicy> {
+    let extra = usize::try_from(reader.varint()?).ok().filter(|extra| *extra <= MAX_EXTRA_FRUITS)?;
+    let mut policy = FruitPolicy::new()
+        .extra(extra)
+        .chance(u32::try_from(reader.varint()?).ok()?);
+    if let Some(ticks) = reader.varint()?.checked_sub(1) {
+        policy = policy.lifetime(u32::try_from(ticks).ok()?);
+    }
+    for kind in FruitKind::ALL {
+        policy = policy.weight(kind, u32::try_from(reader.varint()?).ok()?);
+    }
+    Some(policy)
+}
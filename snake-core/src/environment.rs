@@ -0,0 +1,311 @@
+//! Training agents on the game, in the style of reinforcement learning environments.
+//!
+//! An `Environment` wraps a game built by a `GameBuilder`. `reset` starts an episode with a
+//! seed and `step` plays one tick, returning what the agent observes, its reward and whether
+//! the episode is over. Episodes with the same seed and actions play out the same way.
+//!
+//! Observations are written to a buffer of `f32` kept by the environment, so stepping
+//! allocates nothing. Grids are laid out channel by channel, each channel row by row:
+//!
+//! | channel | 1.0 for          |
+//! |---------|------------------|
+//! | 0       | the snake's body |
+//! | 1       | the snake's head |
+//! | 2       | fruit            |
+//! | 3       | walls            |
+
+use crate::{
+    BuildError, Direction, GameBuilder, GameStatus, Location, Pcg32, RandomNumberGenerator, Snake, Square,
+    VecGame,
+};
+
+/// Channels of a grid observation
+pub const CHANNELS: usize = 4;
+
+/// Where the snake goes next, relative to where it's heading
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Action {
+    Straight,
+    Left,
+    Right,
+}
+
+impl Action {
+    /// Actions in the order of their indices, for agents picking one of 3 outputs
+    pub const ALL: [Action; 3] = [Action::Straight, Action::Left, Action::Right];
+
+    fn turn(self, heading: Direction) -> Direction {
+        match (self, heading) {
+            (Action::Straight, heading) => heading,
+            (Action::Left, Direction::Up) | (Action::Right, Direction::Down) => Direction::Left,
+            (Action::Left, Direction::Down) | (Action::Right, Direction::Up) => Direction::Right,
+            (Action::Left, Direction::Left) | (Action::Right, Direction::Right) => Direction::Down,
+            (Action::Left, Direction::Right) | (Action::Right, Direction::Left) => Direction::Up,
+        }
+    }
+}
+
+/// What the agent gets to see after every step
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Observation {
+    /// The whole board, `CHANNELS * width * height` values
+    Grid,
+    /// The squares up to `radius` away from the head, turned so that the snake heads up,
+    /// `CHANNELS * (2 * radius + 1)²` values. Squares past a solid edge are walls.
+    Window { radius: usize },
+    /// `FEATURES` values: whether there is danger straight ahead, to the left and to the right,
+    /// where the snake is heading (up, down, left, right) and whether the apple is above, below,
+    /// to the left and to the right of the head
+    Features,
+}
+
+/// Number of values of `Observation::Features`
+pub const FEATURES: usize = 11;
+
+impl Observation {
+    /// Number of values observed on a board of `width` by `height` squares
+    pub fn len(self, width: usize, height: usize) -> usize {
+        match self {
+            Observation::Grid => CHANNELS * width * height,
+            Observation::Window { radius } => CHANNELS * (2 * radius + 1) * (2 * radius + 1),
+            Observation::Features => FEATURES,
+        }
+    }
+}
+
+/// Rewards for what happens in a step, they add up
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Rewards {
+    /// For every point scored
+    pub point: f32,
+    /// For losing the game
+    pub death: f32,
+    /// For filling the board
+    pub win: f32,
+    /// For every step, negative to hurry the snake up
+    pub step: f32,
+    /// For every square the head gets closer to the apple, and taken back for every square it
+    /// gets farther away
+    pub closer: f32,
+}
+
+impl Default for Rewards {
+    fn default() -> Self {
+        Rewards {
+            point: 1.0,
+            death: -1.0,
+            win: 1.0,
+            step: 0.0,
+            closer: 0.0,
+        }
+    }
+}
+
+/// A game for an agent to play, see the module documentation
+///
+/// ```rust
+/// use self::snake::{Action, Environment, GameBuilder, Observation, Rewards};
+/// let mut env = Environment::new(GameBuilder::new(10, 10), Observation::Features, Rewards::default()).unwrap();
+///
+/// let observation = env.reset(42).to_vec();
+/// assert_eq!(11, observation.len());
+///
+/// let (_, reward, done) = env.step(Action::Left);
+/// assert_eq!((0.0, false), (reward, done));
+/// ```
+pub struct Environment {
+    game: VecGame<Pcg32>,
+    observation: Observation,
+    rewards: Rewards,
+    buffer: Vec<f32>,
+    max_idle: Option<u32>,
+    idle: u32,
+}
+
+impl Environment {
+    pub fn new(builder: GameBuilder, observation: Observation, rewards: Rewards) -> Result<Self, BuildError> {
+        let game: VecGame<Pcg32> = builder.try_build()?;
+        let len = observation.len(game.board().width(), game.board().height());
+        let mut env = Environment {
+            game,
+            observation,
+            rewards,
+            buffer: vec![0.0; len],
+            max_idle: None,
+            idle: 0,
+        };
+        env.observe();
+        Ok(env)
+    }
+
+    /// Ends episodes in which nothing is eaten for `steps` steps, otherwise a snake going round
+    /// in circles never ends its episode
+    pub fn max_idle(mut self, steps: u32) -> Self {
+        self.max_idle = Some(steps);
+        self
+    }
+
+    /// Number of values in every observation
+    pub fn observation_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// What the agent sees now, the same as returned by the last `reset` or `step`
+    pub fn observation(&self) -> &[f32] {
+        &self.buffer
+    }
+
+    /// Starts a new episode, the same `seed` always places the same fruit
+    pub fn reset(&mut self, seed: u64) -> &[f32] {
+        self.game.restart_with_rng(Pcg32::from_seed(seed));
+        self.idle = 0;
+        self.observe();
+        &self.buffer
+    }
+
+    /// Plays one tick, returning the observation, the reward and whether the episode is over.
+    /// Stepping after the episode is over changes nothing.
+    pub fn step(&mut self, action: Action) -> (&[f32], f32, bool) {
+        if self.is_done() {
+            return (&self.buffer, 0.0, true);
+        }
+
+        let game = &mut self.game;
+        let distance = apple_distance(game);
+        let score = game.score();
+
+        game.set_direction(action.turn(game.direction()));
+        let status = game.advance();
+
+        let points = game.score() - score;
+        let mut reward = self.rewards.step + self.rewards.point * points as f32;
+        match status {
+            GameStatus::Lost => reward += self.rewards.death,
+            GameStatus::Won => reward += self.rewards.win,
+            GameStatus::InProgress | GameStatus::Paused => {
+                if let (Some(before), Some(after)) = (distance, apple_distance(game)) {
+                    if points == 0 {
+                        reward += self.rewards.closer * (before as f32 - after as f32);
+                    }
+                }
+            }
+        }
+
+        self.idle = if points > 0 { 0 } else { self.idle + 1 };
+        self.observe();
+        (&self.buffer, reward, self.is_done())
+    }
+
+    /// Whether the game ended or the snake went too long without eating
+    pub fn is_done(&self) -> bool {
+        self.game.status() != GameStatus::InProgress || self.max_idle.is_some_and(|max| self.idle >= max)
+    }
+
+    /// The game being played, to draw it or to look at more than the observation shows
+    pub fn game(&self) -> &VecGame<Pcg32> {
+        &self.game
+    }
+
+    fn observe(&mut self) {
+        let (game, buffer) = (&self.game, &mut self.buffer);
+        buffer.fill(0.0);
+        match self.observation {
+            Observation::Grid => {
+                let board = game.board();
+                let area = board.width() * board.height();
+                for (location, square) in board.iter() {
+                    let at = location.y as usize * board.width() + location.x as usize;
+                    if let Some(channel) = channel(game, location, square) {
+                        buffer[channel * area + at] = 1.0;
+                    }
+                }
+            }
+            Observation::Window { radius } => {
+                let side = 2 * radius + 1;
+                let radius = radius as i32;
+                for ry in -radius..=radius {
+                    for rx in -radius..=radius {
+                        let at = (ry + radius) as usize * side + (rx + radius) as usize;
+                        let channel = match around_head(game, rx, ry) {
+                            Some(location) => channel(game, location, game.board().at(location)),
+                            None => Some(3),
+                        };
+                        if let Some(channel) = channel {
+                            buffer[channel * side * side + at] = 1.0;
+                        }
+                    }
+                }
+            }
+            Observation::Features => {
+                let heading = game.direction();
+                for (i, action) in Action::ALL.into_iter().enumerate() {
+                    buffer[i] = f32::from(u8::from(is_danger(game, action.turn(heading))));
+                }
+                let headings = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+                for (i, direction) in headings.into_iter().enumerate() {
+                    buffer[3 + i] = f32::from(u8::from(heading == direction));
+                }
+                if let Some(apple) = game.fruit() {
+                    let head = game.head();
+                    let sides = [apple.y < head.y, apple.y > head.y, apple.x < head.x, apple.x > head.x];
+                    for (i, side) in sides.into_iter().enumerate() {
+                        buffer[7 + i] = f32::from(u8::from(side));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn channel(game: &VecGame<Pcg32>, location: Location, square: Square) -> Option<usize> {
+    match square {
+        Square::Snake if location == game.head() => Some(1),
+        Square::Snake => Some(0),
+        Square::Fruit(_) => Some(2),
+        Square::Wall | Square::Player(_) => Some(3),
+        Square::Empty => None,
+    }
+}
+
+/// Square `rx` to the right and `-ry` ahead of the head, `None` past a solid edge
+fn around_head(game: &VecGame<Pcg32>, rx: i32, ry: i32) -> Option<Location> {
+    let head = game.head();
+    let (dx, dy) = match game.direction() {
+        Direction::Up => (rx, ry),
+        Direction::Right => (-ry, rx),
+        Direction::Down => (-rx, -ry),
+        Direction::Left => (ry, -rx),
+    };
+    let board = game.board();
+    game.boundary_mode().apply(
+        Location {
+            x: head.x + dx,
+            y: head.y + dy,
+        },
+        board.width(),
+        board.height(),
+    )
+}
+
+fn is_danger(game: &VecGame<Pcg32>, direction: Direction) -> bool {
+    let board = game.board();
+    match game
+        .boundary_mode()
+        .apply(game.head().move_in(direction), board.width(), board.height())
+    {
+        Some(location) => matches!(board.at(location), Square::Snake | Square::Wall | Square::Player(_)),
+        None => true,
+    }
+}
+
+/// Squares between the head and the apple, going across the edges where the snake can
+fn apple_distance(game: &VecGame<Pcg32>) -> Option<u32> {
+    let (head, apple) = (game.head(), game.fruit()?);
+    let board = game.board();
+    let (width, height) = (board.width() as u32, board.height() as u32);
+    let (dx, dy) = (head.x.abs_diff(apple.x), head.y.abs_diff(apple.y));
+    let (wraps_x, wraps_y) = game.boundary_mode().wraps();
+    let dx = if wraps_x { dx.min(width - dx) } else { dx };
+    let dy = if wraps_y { dy.min(height - dy) } else { dy };
+    Some(dx + dy)
+}
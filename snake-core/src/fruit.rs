@@ -0,0 +1,168 @@
+//! Fruit that does more than growing the snake by one.
+//!
+//! There is always one apple on the board, eating it places the next one and the game is won
+//! when there is no room left for it. A `FruitPolicy` can add up to `MAX_EXTRA_FRUITS` more
+//! fruits of other kinds, they are placed by the game's generator so games with the same seed
+//! and inputs still play out the same way.
+
+use crate::Location;
+
+/// Most fruits a game keeps on the board besides the apple
+pub const MAX_EXTRA_FRUITS: usize = 4;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FruitKind {
+    /// Grows the snake by a segment and scores a point
+    #[default]
+    Apple,
+    /// Grows the snake by a segment and scores 5 points
+    Bonus,
+    /// Grows the snake by 3 segments over the next ticks and scores a point
+    Feast,
+    /// Takes 2 segments off the tail, never the head, and scores nothing
+    Shrink,
+}
+
+impl FruitKind {
+    pub const ALL: [FruitKind; 4] = [FruitKind::Apple, FruitKind::Bonus, FruitKind::Feast, FruitKind::Shrink];
+
+    pub const fn points(self) -> u32 {
+        match self {
+            FruitKind::Bonus => 5,
+            FruitKind::Apple | FruitKind::Feast => 1,
+            FruitKind::Shrink => 0,
+        }
+    }
+
+    /// Segments the snake gains, negative when it loses some
+    pub const fn growth(self) -> i32 {
+        match self {
+            FruitKind::Apple | FruitKind::Bonus => 1,
+            FruitKind::Feast => 3,
+            FruitKind::Shrink => -2,
+        }
+    }
+
+    const fn index(self) -> usize {
+        match self {
+            FruitKind::Apple => 0,
+            FruitKind::Bonus => 1,
+            FruitKind::Feast => 2,
+            FruitKind::Shrink => 3,
+        }
+    }
+}
+
+/// A fruit lying on the board
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fruit {
+    pub at: Location,
+    pub kind: FruitKind,
+    /// Tick at which the fruit disappears if it hasn't been eaten
+    pub expires: Option<u32>,
+}
+
+/// When extra fruits appear, of which kind and for how long
+///
+/// Every tick the snake survives with fewer than `extra` extra fruits on the board, one more
+/// appears with a chance of `chance` percent. Its kind is drawn with the weights of the kinds.
+///
+/// ```rust
+/// use self::snake::{FruitKind, FruitPolicy};
+/// let policy = FruitPolicy::new()
+///     .extra(2)
+///     .chance(10)
+///     .lifetime(40)
+///     .weight(FruitKind::Shrink, 0);
+///
+/// assert_eq!(Some(40), policy.lifetime_ticks());
+/// assert_eq!(FruitPolicy::new(), FruitPolicy::default());
+/// ```
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FruitPolicy {
+    extra: usize,
+    chance: u32,
+    lifetime: Option<u32>,
+    weights: [u32; 4],
+}
+
+impl Default for FruitPolicy {
+    fn default() -> Self {
+        FruitPolicy::new()
+    }
+}
+
+impl FruitPolicy {
+    /// Only the apple, extra fruits appear once `extra` is set
+    pub const fn new() -> Self {
+        FruitPolicy {
+            extra: 0,
+            chance: 5,
+            lifetime: None,
+            weights: [0, 3, 2, 1],
+        }
+    }
+
+    /// Up to 2 extra fruits lasting 60 ticks each
+    pub const fn mixed() -> Self {
+        FruitPolicy::new().extra(2).lifetime(60)
+    }
+
+    /// How many extra fruits can be on the board at once, at most `MAX_EXTRA_FRUITS`
+    pub const fn extra(mut self, extra: usize) -> Self {
+        self.extra = if extra > MAX_EXTRA_FRUITS { MAX_EXTRA_FRUITS } else { extra };
+        self
+    }
+
+    /// Percent chance of an extra fruit appearing on a tick
+    pub const fn chance(mut self, percent: u32) -> Self {
+        self.chance = percent;
+        self
+    }
+
+    /// Extra fruits disappear `ticks` after they appeared, by default they stay until eaten
+    pub const fn lifetime(mut self, ticks: u32) -> Self {
+        self.lifetime = Some(ticks);
+        self
+    }
+
+    /// How likely `kind` is compared to the other kinds, 0 means it never appears as an extra
+    /// fruit
+    pub const fn weight(mut self, kind: FruitKind, weight: u32) -> Self {
+        self.weights[kind.index()] = weight;
+        self
+    }
+
+    pub const fn extra_fruits(&self) -> usize {
+        self.extra
+    }
+
+    pub const fn chance_percent(&self) -> u32 {
+        self.chance
+    }
+
+    pub const fn lifetime_ticks(&self) -> Option<u32> {
+        self.lifetime
+    }
+
+    pub const fn weight_of(&self, kind: FruitKind) -> u32 {
+        self.weights[kind.index()]
+    }
+
+    /// Picks the kind for `roll`, `None` when every weight is 0
+    pub(crate) fn pick(&self, roll: u32) -> Option<FruitKind> {
+        let total = self.weights.iter().fold(0u32, |total, w| total.saturating_add(*w));
+        let mut roll = roll.checked_rem(total)?;
+        FruitKind::ALL.into_iter().find(|kind| {
+            let weight = self.weight_of(*kind);
+            if roll < weight {
+                return true;
+            }
+            roll -= weight;
+            false
+        })
+    }
+}
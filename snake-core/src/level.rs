@@ -0,0 +1,278 @@
+//! Plain text levels.
+//!
+//! A level is a grid of characters with one line per row of the board:
+//!
+//! * `#` - wall
+//! * `O` - snake body segment
+//! * `^`, `v`, `<`, `>` - snake head, pointing in the initial direction
+//! * `F` - fruit spawn point, when there are any fruit is only placed on them
+//! * ` ` or `.` - empty square
+//!
+//! All rows must be equally long. The body is followed starting from the head, so a segment
+//! may only touch the segments directly before and after it.
+
+use core::fmt;
+
+use crate::{Direction, Location};
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum LevelErrorKind {
+    /// There are no squares at all
+    Empty,
+    /// The character has no meaning in a level
+    UnexpectedCharacter(char),
+    /// The row is not as long as the first one
+    RowLength { expected: usize, found: usize },
+    /// There is no head marker
+    MissingHead,
+    /// There is more than one head marker
+    DuplicateHead,
+    /// The square touches too many body segments to tell their order
+    AmbiguousBody,
+    /// The body segment can't be reached from the head
+    DisconnectedBody,
+    /// The head points at the segment right behind it
+    HeadFacesBody,
+}
+
+/// Describes what is wrong with a level and where, lines and columns start at 1
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct LevelError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: LevelErrorKind,
+}
+
+impl LevelError {
+    fn at(location: Location, kind: LevelErrorKind) -> LevelError {
+        LevelError {
+            line: location.y as usize + 1,
+            column: location.x as usize + 1,
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for LevelErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelErrorKind::Empty => write!(f, "level is empty"),
+            LevelErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character {:?}", c),
+            LevelErrorKind::RowLength { expected, found } => {
+                write!(f, "row is {} squares long, expected {}", found, expected)
+            }
+            LevelErrorKind::MissingHead => write!(f, "snake head is missing"),
+            LevelErrorKind::DuplicateHead => write!(f, "snake head is already placed"),
+            LevelErrorKind::AmbiguousBody => write!(f, "order of the body segments is ambiguous"),
+            LevelErrorKind::DisconnectedBody => write!(f, "body segment is not connected to the head"),
+            LevelErrorKind::HeadFacesBody => write!(f, "snake head points at its own body"),
+        }
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+/// A validated level, borrowing the text it was parsed from
+///
+/// ```rust
+/// use self::snake::{Direction, Level, Location};
+/// let level = Level::parse("#####\n#OO>#\n#####").unwrap();
+///
+/// assert_eq!(5, level.width());
+/// assert_eq!(3, level.height());
+/// assert_eq!(Direction::Right, level.direction());
+/// assert_eq!(3, level.snake_length());
+/// assert_eq!(Some(Location::new(1, 1)), level.snake().last());
+/// assert_eq!(12, level.walls().count());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Level<'a> {
+    text: &'a str,
+    width: usize,
+    height: usize,
+    head: Location,
+    direction: Direction,
+    snake_length: usize,
+}
+
+impl<'a> Level<'a> {
+    pub fn parse(text: &'a str) -> Result<Level<'a>, LevelError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut head = None;
+
+        for (y, row) in text.lines().enumerate() {
+            let mut found = 0;
+
+            for (x, c) in row.chars().enumerate() {
+                let location = Location::new(x, y);
+                match c {
+                    '#' | 'O' | 'F' | ' ' | '.' => {}
+                    '^' | 'v' | '<' | '>' => {
+                        if head.is_some() {
+                            return Err(LevelError::at(location, LevelErrorKind::DuplicateHead));
+                        }
+                        head = Some((location, head_direction(c as u8).unwrap()));
+                    }
+                    c => return Err(LevelError::at(location, LevelErrorKind::UnexpectedCharacter(c))),
+                }
+                found += 1;
+            }
+
+            match width {
+                None => width = Some(found),
+                Some(expected) if expected != found => {
+                    return Err(LevelError::at(
+                        Location::new(expected.min(found), y),
+                        LevelErrorKind::RowLength { expected, found },
+                    ))
+                }
+                Some(_) => {}
+            }
+
+            height += 1;
+        }
+
+        let width = match width {
+            Some(width) if width > 0 => width,
+            _ => return Err(LevelError::at(Location::new(0, 0), LevelErrorKind::Empty)),
+        };
+
+        let (head, direction) = match head {
+            Some(head) => head,
+            None => return Err(LevelError::at(Location::new(0, 0), LevelErrorKind::MissingHead)),
+        };
+
+        let mut level = Level {
+            text,
+            width,
+            height,
+            head,
+            direction,
+            snake_length: 0,
+        };
+
+        level.validate_body()?;
+
+        Ok(level)
+    }
+
+    /// The text the level was parsed from
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn snake_length(&self) -> usize {
+        self.snake_length
+    }
+
+    /// Snake locations starting from the head
+    pub fn snake(&self) -> impl Iterator<Item = Location> + '_ {
+        let mut previous = None;
+        let mut current = Some(self.head);
+
+        core::iter::from_fn(move || {
+            let result = current?;
+            current = self.next_segment(previous, result);
+            previous = Some(result);
+            Some(result)
+        })
+    }
+
+    pub fn walls(&self) -> impl Iterator<Item = Location> + '_ {
+        self.locations_of(b'#')
+    }
+
+    pub fn fruit_spawns(&self) -> impl Iterator<Item = Location> + '_ {
+        self.locations_of(b'F')
+    }
+
+    fn validate_body(&mut self) -> Result<(), LevelError> {
+        for (location, square) in self.squares() {
+            let limit = match square {
+                b'O' => 2,
+                s if head_direction(s).is_some() => 1,
+                _ => continue,
+            };
+
+            if self.body_neighbours(location).count() > limit {
+                return Err(LevelError::at(location, LevelErrorKind::AmbiguousBody));
+            }
+        }
+
+        self.snake_length = self.snake().count();
+
+        if self.snake_length != self.locations_of(b'O').count() + 1 {
+            let disconnected = self
+                .locations_of(b'O')
+                .find(|l| !self.snake().any(|s| s == *l))
+                .unwrap();
+            return Err(LevelError::at(disconnected, LevelErrorKind::DisconnectedBody));
+        }
+
+        if self.snake().nth(1) == Some(self.head.move_in(self.direction)) {
+            return Err(LevelError::at(self.head, LevelErrorKind::HeadFacesBody));
+        }
+
+        Ok(())
+    }
+
+    fn next_segment(&self, previous: Option<Location>, current: Location) -> Option<Location> {
+        self.body_neighbours(current)
+            .find(|l| Some(*l) != previous && self.at(*l) == b'O')
+    }
+
+    fn body_neighbours(&self, location: Location) -> impl Iterator<Item = Location> + '_ {
+        DIRECTIONS
+            .iter()
+            .map(move |d| location.move_in(*d))
+            .filter(move |l| l.is_within(self.width, self.height))
+            .filter(move |l| self.at(*l) == b'O' || head_direction(self.at(*l)).is_some())
+    }
+
+    fn at(&self, location: Location) -> u8 {
+        self.text.lines().nth(location.y as usize).unwrap().as_bytes()[location.x as usize]
+    }
+
+    fn squares(&self) -> impl Iterator<Item = (Location, u8)> + '_ {
+        self.text.lines().enumerate().flat_map(|(y, row)| {
+            row.bytes()
+                .enumerate()
+                .map(move |(x, square)| (Location::new(x, y), square))
+        })
+    }
+
+    fn locations_of(&self, square: u8) -> impl Iterator<Item = Location> + '_ {
+        self.squares()
+            .filter(move |(_, s)| *s == square)
+            .map(|(l, _)| l)
+    }
+}
+
+fn head_direction(square: u8) -> Option<Direction> {
+    match square {
+        b'^' => Some(Direction::Up),
+        b'v' => Some(Direction::Down),
+        b'<' => Some(Direction::Left),
+        b'>' => Some(Direction::Right),
+        _ => None,
+    }
+}
@@ -1,11 +1,48 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use core::convert::TryFrom;
-use core::ops::DerefMut;
 
 pub use paste;
 
+mod builder;
+mod controller;
+mod encoding;
+#[cfg(feature = "std")]
+mod environment;
+mod fruit;
+mod level;
+mod multi;
+mod replay;
+mod rng;
+mod snapshot;
+mod speed;
+mod storage;
+#[cfg(feature = "std")]
+mod stream;
+
+pub use builder::{BuildError, GameBuilder};
+pub use controller::{Controller, Greedy, Hamiltonian};
+#[cfg(feature = "std")]
+pub use environment::{Action, Environment, Observation, Rewards, CHANNELS, FEATURES};
+pub use fruit::{Fruit, FruitKind, FruitPolicy, MAX_EXTRA_FRUITS};
+pub use level::{Level, LevelError, LevelErrorKind};
+#[cfg(feature = "std")]
+pub use multi::VecMultiGame;
+pub use multi::{GenericMultiGame, MultiGame, MultiStatus, MAX_PLAYERS};
+pub use replay::{FixedLog, InputLog, Recorder, Replay, ReplayError, ReplayStart};
+pub use rng::Pcg32;
+#[cfg(feature = "serde")]
+pub use snapshot::GameState;
+pub use snapshot::SnapshotError;
+pub use speed::{Speed, SpeedCurve, SpeedMetric};
+#[cfg(feature = "std")]
+pub use storage::Dynamic;
+pub use storage::{Fixed, Storage};
+#[cfg(feature = "std")]
+pub use stream::{Frame, StreamReader, StreamWriter};
+
 #[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     Up,
     Down,
@@ -13,21 +50,65 @@ pub enum Direction {
     Right,
 }
 
+impl Direction {
+    /// ```rust
+    /// use self::snake::Direction;
+    /// assert_eq!(Direction::Down, Direction::Up.opposite());
+    /// assert_eq!(Direction::Right, Direction::Left.opposite());
+    /// ```
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
 pub trait Board {
     fn width(&self) -> usize;
     fn height(&self) -> usize;
     fn at(&self, location: Location) -> Square;
     fn at_mut(&mut self, location: &Location) -> &mut Square;
-    fn iter(&self) -> BoardIterator;
+    fn iter(&self) -> BoardIterator<'_>;
 }
 
 pub trait Snake {
-    fn board(&mut self) -> &dyn Board;
-    fn advance(&mut self) -> GameStatus;
+    fn board(&self) -> &dyn Board;
+    fn advance(&mut self) -> GameStatus {
+        self.advance_with_events(&mut |_| {})
+    }
+    /// Advances the game passing everything that happened during the tick to `events`
+    fn advance_with_events(&mut self, events: &mut dyn FnMut(GameEvent)) -> GameStatus;
+    /// Queues a turn, see `MAX_QUEUED_TURNS`, turns are ignored while the game is paused
     fn set_direction(&mut self, direction: Direction);
+    /// Stops the game until `resume`, `advance` does nothing meanwhile
+    fn pause(&mut self);
+    fn resume(&mut self);
+    /// Starts over with the snake the game started with, the walls stay and fruit is placed
+    /// by a generator seeded from the current one
+    fn restart(&mut self);
+    /// Points scored so far, see `FruitKind::points`
+    fn score(&self) -> u32;
+    fn length(&self) -> usize;
+    /// Segment `index` counting from the tail, the head is at `length() - 1`
+    fn segment(&self, index: usize) -> Option<Location>;
+    fn head(&self) -> Location;
+    fn direction(&self) -> Direction;
+    /// Direction the snake will head in after the next tick
+    fn pending_direction(&self) -> Direction;
+    fn status(&self) -> GameStatus;
+    /// Number of ticks played while the game was in progress
+    fn ticks(&self) -> u32;
+    /// Current apple, there is none once the game is over or while extra fruits cover every
+    /// free square
+    fn fruit(&self) -> Option<Location>;
+    fn boundary_mode(&self) -> BoundaryMode;
 }
 
 #[derive(PartialEq, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Location {
     pub x: i32,
     pub y: i32,
@@ -85,65 +166,144 @@ impl Location {
     /// assert_eq!(1, Location{x: 4, y: 2}.wrap(3,3).x);
     /// assert_eq!(2, Location{x: 4, y: 2}.wrap(3,3).y);
     /// assert_eq!(1, Location{x: -2, y: 2}.wrap(3,3).x);
+    /// assert_eq!(0, Location{x: -6, y: 2}.wrap(3,3).x);
     /// ```
     pub fn wrap(mut self, max_width: usize, max_height: usize) -> Location {
-        if self.x >= max_width as i32 {
-            self.x %= max_width as i32;
-        }
+        self.x = self.x.rem_euclid(max_width as i32);
+        self.y = self.y.rem_euclid(max_height as i32);
+        self
+    }
 
-        if self.x < 0 as i32 {
-            self.x = max_width as i32 + (self.x % max_width as i32);
-        }
+    /// ```rust
+    /// use self::snake::Location;
+    /// assert!(Location{x: 0, y: 0}.is_within(3, 3));
+    /// assert!(Location{x: 2, y: 2}.is_within(3, 3));
+    /// assert!(!Location{x: 3, y: 0}.is_within(3, 3));
+    /// assert!(!Location{x: 0, y: -1}.is_within(3, 3));
+    /// ```
+    pub fn is_within(self, width: usize, height: usize) -> bool {
+        self.x >= 0 && self.x < width as i32 && self.y >= 0 && self.y < height as i32
+    }
+}
 
-        if self.y >= max_height as i32 {
-            self.y %= max_height as i32;
-        }
+/// Decides what happens when the snake leaves the board
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoundaryMode {
+    /// Every edge wraps around to the opposite one
+    #[default]
+    Wrap,
+    /// Every edge is a wall, leaving the board loses the game
+    Solid,
+    /// Left and right edges wrap, top and bottom are solid
+    WrapHorizontally,
+    /// Top and bottom edges wrap, left and right are solid
+    WrapVertically,
+}
+
+impl BoundaryMode {
+    /// Brings `location` back onto the board or returns `None` if it crossed a solid edge
+    ///
+    /// ```rust
+    /// use self::snake::{BoundaryMode, Location};
+    /// assert_eq!(Some(Location{x: 0, y: 1}), BoundaryMode::Wrap.apply(Location{x: 3, y: 1}, 3, 3));
+    /// assert_eq!(None, BoundaryMode::Solid.apply(Location{x: 3, y: 1}, 3, 3));
+    /// assert_eq!(Some(Location{x: 1, y: 1}), BoundaryMode::Solid.apply(Location{x: 1, y: 1}, 3, 3));
+    /// assert_eq!(Some(Location{x: 2, y: 1}), BoundaryMode::WrapHorizontally.apply(Location{x: -1, y: 1}, 3, 3));
+    /// assert_eq!(None, BoundaryMode::WrapHorizontally.apply(Location{x: 1, y: -1}, 3, 3));
+    /// assert_eq!(Some(Location{x: 1, y: 2}), BoundaryMode::WrapVertically.apply(Location{x: 1, y: -1}, 3, 3));
+    /// assert_eq!(None, BoundaryMode::WrapVertically.apply(Location{x: 3, y: 1}, 3, 3));
+    /// ```
+    pub fn apply(self, location: Location, width: usize, height: usize) -> Option<Location> {
+        let (wrap_x, wrap_y) = self.wraps();
+
+        let outside_x = location.x < 0 || location.x >= width as i32;
+        let outside_y = location.y < 0 || location.y >= height as i32;
 
-        if self.y < 0 as i32 {
-            self.y = max_height as i32 + (self.y % max_height as i32);
+        if (outside_x && !wrap_x) || (outside_y && !wrap_y) {
+            None
+        } else {
+            Some(location.wrap(width, height))
         }
+    }
 
-        self
+    /// Whether the left and right, and the top and bottom edges wrap around
+    pub fn wraps(self) -> (bool, bool) {
+        match self {
+            BoundaryMode::Wrap => (true, true),
+            BoundaryMode::Solid => (false, false),
+            BoundaryMode::WrapHorizontally => (true, false),
+            BoundaryMode::WrapVertically => (false, true),
+        }
     }
 }
 
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Square {
-    Fruit,
+    Fruit(FruitKind),
+    #[default]
     Empty,
     Snake,
+    Wall,
+    /// Segment of the snake of player `n` in a `MultiGame`, `n` is below `MAX_PLAYERS`
+    Player(u8),
+}
+
+/// What the snake ran into
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Collision {
+    /// A solid edge of the board
+    Boundary,
+    Wall,
+    Snake,
+    /// The head of another snake entering the same square, only in a `MultiGame`
+    Head,
 }
 
 #[derive(PartialEq, Copy, Clone, Debug)]
+pub enum GameEvent {
+    FruitEaten { at: Location, kind: FruitKind },
+    /// The snake grew to `length` segments
+    Grew { length: usize },
+    /// The snake lost segments down to `length`
+    Shrank { length: usize },
+    FruitSpawned { at: Location },
+    /// An extra fruit nobody ate disappeared
+    FruitExpired { at: Location },
+    DirectionChanged { from: Direction, to: Direction },
+    /// The head tried to enter `at`, which lies outside the board for `Collision::Boundary`
+    Collided { at: Location, with: Collision },
+    Won,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameStatus {
     InProgress,
+    Paused,
     Lost,
     Won,
 }
 
-impl Default for Square {
-    fn default() -> Self {
-        Square::Empty
-    }
-}
-
-
-pub trait FixedSizedArray<T>: Default + DerefMut<Target = [T]> {}
-impl<T, A> FixedSizedArray<T> for A where A: Default + DerefMut<Target = [T]> {}
-
-pub struct FixedSizeBoard<const SIZE: usize>
+/// Board with its squares in a `Storage` array, row by row
+pub struct GridBoard<S: Storage>
 {
-    data: [Square; SIZE],
+    data: S::Array<Square>,
     width: usize,
     height: usize,
 }
 
-impl<const SIZE: usize> FixedSizeBoard<SIZE>
+pub type FixedSizeBoard<const SIZE: usize> = GridBoard<Fixed<SIZE>>;
+/// Board of any size, allocated on the heap
+#[cfg(feature = "std")]
+pub type VecBoard = GridBoard<Dynamic>;
+
+impl<S: Storage> GridBoard<S>
 {
     pub fn new(width: usize, height: usize) -> Self {
-        assert_eq!(SIZE, width * height);
         Self {
-            data: [Square::default(); SIZE],
+            data: S::array(Square::default(), width * height),
             width,
             height,
         }
@@ -151,7 +311,7 @@ impl<const SIZE: usize> FixedSizeBoard<SIZE>
 
 }
 
-impl<const SIZE: usize> Board for FixedSizeBoard<SIZE>
+impl<S: Storage> Board for GridBoard<S>
 {
     fn width(&self) -> usize {
         self.width
@@ -198,163 +358,605 @@ impl<'a> Iterator for BoardIterator<'a> {
     }
 }
 
-pub struct Game<const SIZE: usize, R>
+/// A square that looks different than when it was last reported
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Change {
+    pub location: Location,
+    pub old: Square,
+    pub new: Square,
+}
+
+/// Yields the squares of a board that differ from what was shown so far, updating `shown`
+/// along the way. Squares left unvisited when the iterator is dropped are reported next time.
+pub struct Changes<'a> {
+    squares: BoardIterator<'a>,
+    shown: &'a mut dyn Board,
+}
+
+impl<'a> Changes<'a> {
+    pub fn new(board: &'a dyn Board, shown: &'a mut dyn Board) -> Self {
+        assert_eq!((board.width(), board.height()), (shown.width(), shown.height()));
+        Changes {
+            squares: board.iter(),
+            shown,
+        }
+    }
+}
+
+impl<'a> Iterator for Changes<'a> {
+    type Item = Change;
+    fn next(&mut self) -> Option<Self::Item> {
+        for (location, new) in self.squares.by_ref() {
+            let old = self.shown.at_mut(&location);
+            if *old != new {
+                let change = Change { location, old: *old, new };
+                *old = new;
+                return Some(change);
+            }
+        }
+
+        None
+    }
+}
+
+/// How many turns `set_direction` keeps for the following ticks, more are ignored
+///
+/// Pressing two keys quickly within a single tick turns the snake on two consecutive ticks
+/// instead of losing the first key.
+pub const MAX_QUEUED_TURNS: usize = 3;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+struct TurnQueue {
+    turns: [Direction; MAX_QUEUED_TURNS],
+    len: usize,
+}
+
+impl Default for TurnQueue {
+    fn default() -> Self {
+        TurnQueue {
+            turns: [Direction::Up; MAX_QUEUED_TURNS],
+            len: 0,
+        }
+    }
+}
+
+impl TurnQueue {
+    /// Queues `direction` unless the queue is full or it wouldn't turn the snake once the
+    /// queued turns are applied, which includes reversing into itself
+    fn push(&mut self, direction: Direction, current: Direction) -> bool {
+        let last = self.as_slice().last().copied().unwrap_or(current);
+        if self.len == MAX_QUEUED_TURNS || direction == last || direction == last.opposite() {
+            return false;
+        }
+
+        self.turns[self.len] = direction;
+        self.len += 1;
+        true
+    }
+
+    fn pop(&mut self) -> Option<Direction> {
+        let first = self.as_slice().first().copied()?;
+        self.turns.copy_within(1.., 0);
+        self.len -= 1;
+        Some(first)
+    }
+
+    fn as_slice(&self) -> &[Direction] {
+        &self.turns[..self.len]
+    }
+}
+
+/// The game with its board and snake kept in `S`, see `Game` and `VecGame`
+pub struct GenericGame<S, R>
 where
+    S: Storage,
     R: RandomNumberGenerator,
 {
     width: usize,
     height: usize,
-    snake: [Location; SIZE],
+    /// Circular buffer of snake segments, from the tail at `snake_tail` up to the head
+    snake: S::Array<Location>,
+    snake_tail: usize,
     snake_size: usize,
     current_direction: Direction,
-    next_direction: Direction,
-    fruit: Location,
+    turns: TurnQueue,
+    /// The snake and direction `restart` goes back to, from the tail to the head
+    initial_snake: S::Array<Location>,
+    initial_length: usize,
+    initial_direction: Direction,
+    /// The apple, none while extra fruits cover the only squares it could go to
+    fruit: Option<Location>,
+    fruit_policy: FruitPolicy,
+    /// Fruits besides the apple, see `FruitPolicy`
+    extra_fruits: [Option<Fruit>; MAX_EXTRA_FRUITS],
+    /// Segments still to be added from the tail end as the snake moves
+    pending_growth: usize,
     status: GameStatus,
+    score: u32,
+    ticks: u32,
+    boundary_mode: BoundaryMode,
+    fruit_spawns: Option<S::Array<bool>>,
     rng: R,
-    board: FixedSizeBoard<SIZE>,
+    /// Kept up to date as the game goes, it is also used to look up what occupies a square
+    board: GridBoard<S>,
+    /// The board as it was when `changes` was last called
+    shown: GridBoard<S>,
 }
 
-pub trait RandomNumberGenerator: Default {
+/// Game on a board of `SIZE` squares, which must be `width * height`, usable without `std`
+pub type Game<const SIZE: usize, R> = GenericGame<Fixed<SIZE>, R>;
+/// Game on a board sized at runtime, allocated on the heap
+#[cfg(feature = "std")]
+pub type VecGame<R> = GenericGame<Dynamic, R>;
+
+/// Source of randomness for fruit placement, `Pcg32` is a good default
+///
+/// The same seed must always give the same numbers, games are replayed that way.
+pub trait RandomNumberGenerator {
+    fn from_seed(seed: u64) -> Self
+    where
+        Self: Sized;
     fn next(&mut self) -> u32;
+    /// Everything needed to continue the same sequence with `from_state`, used by snapshots
+    fn state(&self) -> u128;
+    fn from_state(state: u128) -> Self
+    where
+        Self: Sized;
 }
 
-impl<const SIZE: usize, R> Game<SIZE, R>
+/// Seed used by the constructors that don't take a generator
+pub const DEFAULT_SEED: u64 = 0;
+
+impl<S, R> GenericGame<S, R>
 where
+    S: Storage,
     R: RandomNumberGenerator,
 {
-    pub fn new(width: usize, height: usize) -> Game<SIZE, R> {
-        assert_eq!(SIZE, width * height);
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::with_boundary_mode(width, height, BoundaryMode::default())
+    }
 
-        let center_x = (width / 2) as i32;
-        let center_y = (height / 2) as i32;
+    pub fn with_boundary_mode(width: usize, height: usize, boundary_mode: BoundaryMode) -> Self {
+        Self::with_rng(width, height, boundary_mode, R::from_seed(DEFAULT_SEED))
+    }
 
-        let mut snake = [Location::default(); SIZE];
-        snake[1] = Location::new(center_x, center_y);
-        snake[0] = Location::new(center_x - 1, center_y);
+    /// Use `GameBuilder` to change anything else about the start
+    pub fn with_rng(width: usize, height: usize, boundary_mode: BoundaryMode, rng: R) -> Self {
+        GameBuilder::new(width, height)
+            .boundary_mode(boundary_mode)
+            .build_with_rng(rng)
+    }
 
-        let mut game = Game {
+    /// Creates a game with the walls, snake and fruit spawn points described by `level`
+    ///
+    /// ```rust
+    /// use self::snake::{BoundaryMode, Game, Level, Pcg32};
+    /// let level = Level::parse("#####\n#OO>#\n#F###").unwrap();
+    /// let game = Game::<{ 5 * 3 }, Pcg32>::from_level(&level, BoundaryMode::Wrap);
+    /// ```
+    pub fn from_level(level: &Level, boundary_mode: BoundaryMode) -> Self {
+        Self::from_level_with_rng(level, boundary_mode, R::from_seed(DEFAULT_SEED))
+    }
+
+    pub fn from_level_with_rng(level: &Level, boundary_mode: BoundaryMode, rng: R) -> Self {
+        GameBuilder::new(level.width(), level.height())
+            .level(level)
+            .boundary_mode(boundary_mode)
+            .build_with_rng(rng)
+    }
+
+    fn empty(width: usize, height: usize, boundary_mode: BoundaryMode, direction: Direction, rng: R) -> Self {
+        GenericGame {
             width,
             height,
-            snake,
-            snake_size: 2,
-            current_direction: Direction::Right,
-            next_direction: Direction::Right,
-            fruit: Location::new(0, 0),
+            snake: S::array(Location::default(), width * height),
+            snake_tail: 0,
+            snake_size: 0,
+            current_direction: direction,
+            turns: TurnQueue::default(),
+            initial_snake: S::array(Location::default(), width * height),
+            initial_length: 0,
+            initial_direction: direction,
+            fruit: None,
+            fruit_policy: FruitPolicy::new(),
+            extra_fruits: [None; MAX_EXTRA_FRUITS],
+            pending_growth: 0,
             status: GameStatus::InProgress,
-            rng: R::default(),
-            board: FixedSizeBoard::<SIZE>::new(width, height),
-        };
+            score: 0,
+            ticks: 0,
+            boundary_mode,
+            fruit_spawns: None,
+            rng,
+            board: GridBoard::new(width, height),
+            shown: GridBoard::new(width, height),
+        }
+    }
 
-        game.fruit = game.place_new_fruit().unwrap();
+    /// Squares that changed since the previous call, starting from a board of empty squares.
+    ///
+    /// Meant for renderers that only redraw what is different. The whole board is compared,
+    /// but nothing is drawn for squares that stayed the same.
+    pub fn changes(&mut self) -> Changes<'_> {
+        Changes::new(&self.board, &mut self.shown)
+    }
 
-        game
+    /// Makes the next call to `changes` compare against an empty board again,
+    /// e.g. after the screen was cleared
+    pub fn reset_changes(&mut self) {
+        self.shown = GridBoard::new(self.width, self.height);
+    }
+
+    fn respawn_fruit(&mut self) -> GameStatus {
+        self.fruit = self.place_new_fruit();
+        match self.fruit {
+            Some(fruit) => {
+                *self.board.at_mut(&fruit) = Square::Fruit(FruitKind::Apple);
+                GameStatus::InProgress
+            }
+            // The snake can still eat the extra fruits, the apple comes back once one is gone
+            None if self.board.data.as_ref().iter().any(|s| matches!(s, Square::Fruit(_))) => GameStatus::InProgress,
+            None => GameStatus::Won,
+        }
+    }
+
+    /// Like `respawn_fruit` but also reports where the apple went or that the game is won
+    fn respawn_fruit_with_events(&mut self, events: &mut dyn FnMut(GameEvent)) -> GameStatus {
+        let status = self.respawn_fruit();
+        match (status, self.fruit) {
+            (GameStatus::Won, _) => events(GameEvent::Won),
+            (_, Some(at)) => events(GameEvent::FruitSpawned { at }),
+            _ => {}
+        }
+        status
+    }
+
+    /// Turns `location` into an obstacle that kills the snake on contact.
+    ///
+    /// Returns `false` and leaves the board untouched if the snake occupies the square.
+    /// The apple is moved elsewhere, an extra fruit lying there is gone.
+    pub fn add_wall(&mut self, location: Location) -> bool {
+        if self.is_snake(location) {
+            return false;
+        }
+
+        let had_fruit = self.fruit() == Some(location);
+        self.take_extra_fruit(location);
+        *self.board.at_mut(&location) = Square::Wall;
+
+        if had_fruit {
+            self.status = self.respawn_fruit();
+        }
+
+        true
+    }
+
+    fn is_wall(&self, location: Location) -> bool {
+        self.board.at(location) == Square::Wall
+    }
+
+    fn is_snake(&self, location: Location) -> bool {
+        self.board.at(location) == Square::Snake
+    }
+
+    fn index(&self, location: Location) -> usize {
+        location.y as usize * self.width + location.x as usize
+    }
+
+    /// Number of squares on the board, which is also the most segments the snake can have
+    fn size(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn add_fruit_spawn(&mut self, location: Location) {
+        let index = self.index(location);
+        let size = self.size();
+        self.fruit_spawns.get_or_insert_with(|| S::array(false, size))[index] = true;
     }
 
     fn place_new_fruit(&mut self) -> Option<Location> {
         let fruit = Location::new(self.rng.next() as i32, self.rng.next() as i32).wrap(self.width, self.height);
+        let is_free = |l: Location| self.board.at(l) == Square::Empty;
 
-        place_new_fruit(fruit, self.width, self.height, self.snake())
+        self.fruit_spawns
+            .as_ref()
+            .and_then(|spawns| place_new_fruit(fruit, self.width, self.height, |l| spawns[self.index(l)] && is_free(l)))
+            .or_else(|| place_new_fruit(fruit, self.width, self.height, is_free))
     }
 
-    fn eat_the_fruit(&mut self) {
-        self.snake[self.snake_size] = self.fruit;
-        self.snake_size += 1;
+    fn eat_the_fruit(&mut self, at: Location) {
+        self.push_head(at);
+        self.score += FruitKind::Apple.points();
     }
 
-    fn snake(&self) -> &[Location] {
-        &self.snake[0..self.snake_size]
+    /// Every fruit on the board, the apple first
+    pub fn fruits(&self) -> impl Iterator<Item = Fruit> + '_ {
+        let apple = |at| Fruit {
+            at,
+            kind: FruitKind::Apple,
+            expires: None,
+        };
+        self.fruit().map(apple).into_iter().chain(self.extra_fruits.iter().flatten().copied())
     }
 
-    fn snake_mut(&mut self) -> &mut [Location] {
-        &mut self.snake[0..self.snake_size]
+    pub fn fruit_policy(&self) -> FruitPolicy {
+        self.fruit_policy
     }
 
-    fn move_snake_and_get_status(&mut self) -> GameStatus {
-        self.change_direction();
+    /// Removes the extra fruit at `location` from the game, the board is left as it is
+    fn take_extra_fruit(&mut self, location: Location) -> Option<Fruit> {
+        self.extra_fruits.iter_mut().find(|f| matches!(f, Some(f) if f.at == location))?.take()
+    }
+
+    fn clear_extra_fruits(&mut self) {
+        for fruit in self.extra_fruits.iter_mut().filter_map(Option::take) {
+            *self.board.at_mut(&fruit.at) = Square::Empty;
+        }
+    }
 
-        match self.calcualte_new_head_location() {
-            new_location if self.fruit == new_location => {
-                self.eat_the_fruit();
+    fn eat_extra_fruit(&mut self, fruit: Fruit, events: &mut dyn FnMut(GameEvent)) {
+        *self.board.at_mut(&fruit.at) = Square::Empty;
+        self.score += fruit.kind.points();
+        events(GameEvent::FruitEaten { at: fruit.at, kind: fruit.kind });
 
-                match self.place_new_fruit() {
-                    Some(location) => {
-                        self.fruit = location;
-                        GameStatus::InProgress
-                    }
-                    None => GameStatus::Won,
+        match usize::try_from(fruit.kind.growth()) {
+            Ok(growth) => {
+                self.pending_growth += growth;
+                self.move_snake_in_current_direction(fruit.at, events);
+            }
+            Err(_) => {
+                let shrink = fruit.kind.growth().unsigned_abs() as usize;
+                self.move_snake_in_current_direction(fruit.at, events);
+                let cancelled = shrink.min(self.pending_growth);
+                self.pending_growth -= cancelled;
+                for _ in 0..(shrink - cancelled).min(self.snake_size - 1) {
+                    self.pop_tail();
                 }
+                events(GameEvent::Shrank { length: self.snake_size });
             }
-            new_location if self.snake().contains(&new_location) => GameStatus::Lost,
-            new_location => {
-                self.move_snake_in_current_direction(new_location);
+        }
+    }
+
+    /// Removes extra fruits that expired and maybe adds a new one
+    fn update_extra_fruits(&mut self, events: &mut dyn FnMut(GameEvent)) {
+        let ticks = self.ticks;
+        for slot in &mut self.extra_fruits {
+            if let Some(fruit) = slot.filter(|f| f.expires.is_some_and(|expires| expires <= ticks)) {
+                *slot = None;
+                *self.board.at_mut(&fruit.at) = Square::Empty;
+                events(GameEvent::FruitExpired { at: fruit.at });
+            }
+        }
+
+        // The apple goes first when a square frees up
+        let count = self.extra_fruits.iter().flatten().count();
+        if self.fruit.is_none()
+            || count >= self.fruit_policy.extra_fruits()
+            || self.rng.next() % 100 >= self.fruit_policy.chance_percent()
+        {
+            return;
+        }
+        let Some(kind) = self.fruit_policy.pick(self.rng.next()) else {
+            return;
+        };
+        let Some(at) = self.place_new_fruit() else {
+            return;
+        };
+
+        let fruit = Fruit {
+            at,
+            kind,
+            expires: self.fruit_policy.lifetime_ticks().map(|lifetime| ticks.saturating_add(lifetime)),
+        };
+        if let Some(slot) = self.extra_fruits.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(fruit);
+            *self.board.at_mut(&at) = Square::Fruit(kind);
+            events(GameEvent::FruitSpawned { at });
+        }
+    }
+
+    fn push_head(&mut self, location: Location) {
+        let head = (self.snake_tail + self.snake_size) % self.size();
+        self.snake[head] = location;
+        self.snake_size += 1;
+        *self.board.at_mut(&location) = Square::Snake;
+    }
+
+    fn pop_tail(&mut self) {
+        *self.board.at_mut(&self.snake[self.snake_tail]) = Square::Empty;
+        self.snake_tail = (self.snake_tail + 1) % self.size();
+        self.snake_size -= 1;
+    }
+
+    /// A lost game only shows the walls
+    fn clear_snake_and_fruit(&mut self) {
+        for i in 0..self.snake_size {
+            let location = self.snake[(self.snake_tail + i) % self.size()];
+            *self.board.at_mut(&location) = Square::Empty;
+        }
+        if let Some(fruit) = self.fruit {
+            *self.board.at_mut(&fruit) = Square::Empty;
+        }
+        self.clear_extra_fruits();
+    }
+
+    fn move_snake_and_get_status(&mut self, events: &mut dyn FnMut(GameEvent)) -> GameStatus {
+        self.change_direction(events);
+
+        let head = self.head().move_in(self.current_direction);
+        let collided = |events: &mut dyn FnMut(GameEvent), at, with| {
+            events(GameEvent::Collided { at, with });
+            GameStatus::Lost
+        };
+
+        match self.boundary_mode.apply(head, self.width, self.height) {
+            None => collided(events, head, Collision::Boundary),
+            Some(new_location) if self.is_wall(new_location) => collided(events, new_location, Collision::Wall),
+            Some(new_location) if self.fruit == Some(new_location) => {
+                self.eat_the_fruit(new_location);
+                events(GameEvent::FruitEaten { at: new_location, kind: FruitKind::Apple });
+                events(GameEvent::Grew { length: self.snake_size });
+
+                self.respawn_fruit_with_events(events)
+            }
+            Some(new_location) if self.is_snake(new_location) => collided(events, new_location, Collision::Snake),
+            Some(new_location) => {
+                match self.take_extra_fruit(new_location) {
+                    Some(fruit) => self.eat_extra_fruit(fruit, events),
+                    None => self.move_snake_in_current_direction(new_location, events),
+                }
                 GameStatus::InProgress
             }
         }
     }
 
-    fn calcualte_new_head_location(&self) -> Location {
-        self.snake()
-            .last()
-            .unwrap()
-            .move_in(self.current_direction)
-            .wrap(self.width, self.height)
+    /// Moves the head to `new_head`, the tail stays where it is while there is growth pending
+    fn move_snake_in_current_direction(&mut self, new_head: Location, events: &mut dyn FnMut(GameEvent)) {
+        if self.pending_growth > 0 {
+            self.pending_growth -= 1;
+            self.push_head(new_head);
+            events(GameEvent::Grew { length: self.snake_size });
+        } else {
+            self.pop_tail();
+            self.push_head(new_head);
+        }
     }
 
-    fn move_snake_in_current_direction(&mut self, new_head: Location) {
-        for i in 0..self.snake_size - 1 {
-            self.snake[i] = self.snake[i + 1];
+    fn change_direction(&mut self, events: &mut dyn FnMut(GameEvent)) {
+        if let Some(next) = self.turns.pop() {
+            events(GameEvent::DirectionChanged {
+                from: self.current_direction,
+                to: next,
+            });
+            self.current_direction = next;
         }
+    }
 
-        *self.snake_mut().last_mut().unwrap() = new_head;
+    /// Turns waiting to be applied, the first one on the next tick
+    pub fn queued_turns(&self) -> &[Direction] {
+        self.turns.as_slice()
+    }
+
+    /// Makes the current snake and direction the ones `restart` goes back to
+    fn remember_start(&mut self) {
+        for i in 0..self.snake_size {
+            self.initial_snake[i] = self.snake[(self.snake_tail + i) % self.size()];
+        }
+        self.initial_length = self.snake_size;
+        self.initial_direction = self.current_direction;
     }
 
-    fn change_direction(&mut self) {
-        if match (self.next_direction, self.current_direction) {
-            (Direction::Left, Direction::Right) => false,
-            (Direction::Right, Direction::Left) => false,
-            (Direction::Up, Direction::Down) => false,
-            (Direction::Down, Direction::Up) => false,
-            (_, _) => true,
-        } {
-            self.current_direction = self.next_direction;
+    /// Like `restart` but places fruit with `rng`
+    pub fn restart_with_rng(&mut self, rng: R) {
+        for square in self.board.data.as_mut() {
+            if matches!(*square, Square::Snake | Square::Fruit(_)) {
+                *square = Square::Empty;
+            }
+        }
+        self.extra_fruits = [None; MAX_EXTRA_FRUITS];
+        self.pending_growth = 0;
+
+        self.snake_tail = 0;
+        self.snake_size = 0;
+        for i in 0..self.initial_length {
+            self.push_head(self.initial_snake[i]);
         }
+
+        self.current_direction = self.initial_direction;
+        self.turns = TurnQueue::default();
+        self.score = 0;
+        self.ticks = 0;
+        self.rng = rng;
+        self.status = self.respawn_fruit();
     }
 }
 
-impl<const SIZE: usize, R: RandomNumberGenerator> Snake for Game<SIZE, R>
+impl<S: Storage, R: RandomNumberGenerator> Snake for GenericGame<S, R>
 {
-    fn board(&mut self) -> &dyn Board {
-        let mut board = FixedSizeBoard::<SIZE>::new(self.width, self.height);
-
-        match self.status {
-            GameStatus::InProgress => {
-                *board.at_mut(&self.fruit) = Square::Fruit;
+    fn board(&self) -> &dyn Board {
+        &self.board
+    }
 
-                self.snake().iter().for_each(|l| {
-                    *board.at_mut(l) = Square::Snake;
-                });
+    fn advance_with_events(&mut self, events: &mut dyn FnMut(GameEvent)) -> GameStatus {
+        if self.status == GameStatus::InProgress {
+            self.ticks += 1;
+            self.status = self.move_snake_and_get_status(events);
+            if self.status == GameStatus::InProgress && self.fruit.is_none() {
+                self.status = self.respawn_fruit_with_events(events);
             }
-            GameStatus::Won => {
-                self.snake.iter().for_each(|l| {
-                    *board.at_mut(l) = Square::Snake;
-                });
+
+            match self.status {
+                GameStatus::InProgress => self.update_extra_fruits(events),
+                GameStatus::Lost => self.clear_snake_and_fruit(),
+                _ => self.clear_extra_fruits(),
             }
-            GameStatus::Lost => {}
         }
+        self.status
+    }
 
-        self.board = board;
-        &self.board
+    fn set_direction(&mut self, direction: Direction) {
+        if self.status != GameStatus::Paused {
+            self.turns.push(direction, self.current_direction);
+        }
     }
 
-    fn advance(&mut self) -> GameStatus {
+    fn pause(&mut self) {
         if self.status == GameStatus::InProgress {
-            self.status = self.move_snake_and_get_status()
+            self.status = GameStatus::Paused;
+        }
+    }
+
+    fn resume(&mut self) {
+        if self.status == GameStatus::Paused {
+            self.status = GameStatus::InProgress;
         }
+    }
+
+    fn restart(&mut self) {
+        let seed = (u64::from(self.rng.next()) << 32) | u64::from(self.rng.next());
+        self.restart_with_rng(R::from_seed(seed));
+    }
+
+    fn score(&self) -> u32 {
+        self.score
+    }
+
+    fn length(&self) -> usize {
+        self.snake_size
+    }
+
+    fn segment(&self, index: usize) -> Option<Location> {
+        (index < self.snake_size).then(|| self.snake[(self.snake_tail + index) % self.size()])
+    }
+
+    fn head(&self) -> Location {
+        self.snake[(self.snake_tail + self.snake_size - 1) % self.size()]
+    }
+
+    fn direction(&self) -> Direction {
+        self.current_direction
+    }
+
+    fn pending_direction(&self) -> Direction {
+        self.turns.as_slice().first().copied().unwrap_or(self.current_direction)
+    }
+
+    fn status(&self) -> GameStatus {
         self.status
     }
 
-    fn set_direction(&mut self, direction: Direction) {
-        self.next_direction = direction;
+    fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    fn fruit(&self) -> Option<Location> {
+        match self.status {
+            GameStatus::InProgress | GameStatus::Paused => self.fruit,
+            GameStatus::Lost | GameStatus::Won => None,
+        }
+    }
+
+    fn boundary_mode(&self) -> BoundaryMode {
+        self.boundary_mode
     }
 }
 
@@ -362,12 +964,12 @@ fn place_new_fruit(
     expected: Location,
     width: usize,
     height: usize,
-    taken: &[Location],
+    is_free: impl Fn(Location) -> bool,
 ) -> Option<Location> {
     for y in 0..height {
         for x in 0..width {
             let l = Location::new(expected.x + x as i32, expected.y + y as i32).wrap(width, height);
-            if !taken.contains(&l) {
+            if is_free(l) {
                 return Some(l);
             }
         }
@@ -382,6 +984,18 @@ fn place_new_fruit(
 mod tests {
 
     mod test_utils;
+    mod builder;
+    mod controller;
+    mod environment;
+    mod fruit;
+    mod level;
+    mod multi;
+    mod replay;
+    mod rng;
+    mod snapshot;
+    mod speed;
+    mod storage;
+    mod stream;
 
     use super::*;
     use test_utils::*;
@@ -390,6 +1004,14 @@ mod tests {
         Game::<{5 * 5}, HardcodedNumbersGenerator>::new(5, 5)
     }
 
+    fn create_game_with_walls(walls: &[Location]) -> Game<{5 * 5}, HardcodedNumbersGenerator> {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::new(5, 5);
+        for wall in walls {
+            assert!(game.add_wall(*wall));
+        }
+        game
+    }
+
     #[test]
     fn game_is_initialized() {
         Game::<{5 * 5}, HardcodedNumbersGenerator>::new(5, 5);
@@ -397,7 +1019,7 @@ mod tests {
 
     #[test]
     fn at_the_beginning_snake_is_in_the_middle() {
-        let mut game = create_game();
+        let game = create_game();
 
         let expected = board_layout!(
             "     ",
@@ -556,6 +1178,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn two_turns_within_a_tick_are_applied_on_consecutive_ticks() {
+        let mut game = create_game();
+
+        game.set_direction(Direction::Up);
+        game.set_direction(Direction::Left);
+        game.advance();
+        game.advance();
+
+        assert_board!(
+            game.board(),
+            &board_layout!(
+            "     ",
+            " OO  ",
+            "    F",
+            "     ",
+            "     "
+        )
+        );
+    }
+
+    #[test]
+    fn queued_turn_is_checked_against_the_turn_before_it() {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::new(5, 5);
+
+        game.set_direction(Direction::Up);
+        game.set_direction(Direction::Down);
+        game.set_direction(Direction::Up);
+
+        assert_eq!(&[Direction::Up], game.queued_turns());
+    }
+
+    #[test]
+    fn turns_beyond_the_queue_size_are_ignored() {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::new(5, 5);
+
+        for direction in [Direction::Up, Direction::Left, Direction::Down, Direction::Right] {
+            game.set_direction(direction);
+        }
+
+        assert_eq!(&[Direction::Up, Direction::Left, Direction::Down], game.queued_turns());
+        game.advance();
+        assert_eq!(&[Direction::Left, Direction::Down], game.queued_turns());
+    }
+
     #[test]
     fn snake_cant_turn_up_when_its_moving_down() {
         let mut game = create_game();
@@ -797,6 +1464,331 @@ mod tests {
         );
     }
 
+    #[test]
+    fn game_reports_its_state() {
+        let mut game = create_game();
+
+        assert_eq!(0, game.score());
+        assert_eq!(2, game.length());
+        assert_eq!(Location::new(2, 2), game.head());
+        assert_eq!(Direction::Right, game.direction());
+        assert_eq!(GameStatus::InProgress, game.status());
+        assert_eq!(0, game.ticks());
+        assert_eq!(Some(Location::new(4, 2)), game.fruit());
+
+        game.set_direction(Direction::Down);
+        assert_eq!(Direction::Down, game.pending_direction());
+        assert_eq!(Direction::Right, game.direction());
+
+        let mut game = create_game();
+        game.advance();
+        game.advance();
+
+        assert_eq!(1, game.score());
+        assert_eq!(3, game.length());
+        assert_eq!(Location::new(4, 2), game.head());
+        assert_eq!(2, game.ticks());
+        assert_eq!(Some(Location::new(4, 3)), game.fruit());
+    }
+
+    #[test]
+    fn ticks_stop_counting_when_the_game_is_over() {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::with_boundary_mode(5, 5, BoundaryMode::Solid);
+
+        game.set_direction(Direction::Up);
+        game.advance();
+        game.advance();
+        game.advance();
+        game.advance();
+
+        assert_eq!(GameStatus::Lost, game.status());
+        assert_eq!(3, game.ticks());
+        assert_eq!(None, game.fruit());
+    }
+
+    #[test]
+    fn paused_game_stands_still_until_resumed() {
+        let mut game = create_game();
+
+        game.pause();
+        game.set_direction(Direction::Up);
+        assert_eq!(GameStatus::Paused, game.advance());
+        assert_eq!(0, game.ticks());
+        assert_eq!(Some(Location::new(4, 2)), game.fruit());
+
+        game.resume();
+        assert_eq!(GameStatus::InProgress, game.advance());
+
+        assert_board!(
+            game.board(),
+            &board_layout!(
+            "     ",
+            "     ",
+            "  OOF",
+            "     ",
+            "     "
+        )
+        );
+    }
+
+    #[test]
+    fn game_over_cannot_be_paused() {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::with_boundary_mode(5, 5, BoundaryMode::Solid);
+        for _ in 0..3 {
+            game.advance();
+        }
+
+        game.pause();
+        assert_eq!(GameStatus::Lost, game.status());
+        game.resume();
+        assert_eq!(GameStatus::Lost, game.status());
+    }
+
+    #[test]
+    fn restart_brings_back_the_initial_snake() {
+        let mut game = create_game_with_walls(&[Location::new(0, 0)]);
+        game.set_direction(Direction::Up);
+        game.advance();
+        game.advance();
+        game.set_direction(Direction::Left);
+
+        game.restart();
+
+        assert_eq!((GameStatus::InProgress, 0, 0), (game.status(), game.score(), game.ticks()));
+        assert_eq!(Direction::Right, game.pending_direction());
+        assert_eq!(2, game.board().iter().filter(|(_, s)| *s == Square::Snake).count());
+        assert_eq!(Square::Snake, game.board().at(Location::new(1, 2)));
+        assert_eq!(Location::new(2, 2), game.head());
+        assert_eq!(Square::Wall, game.board().at(Location::new(0, 0)));
+        assert!(game.fruit().is_some());
+    }
+
+    #[test]
+    fn changes_report_squares_updated_since_the_last_call() {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::new(5, 5);
+
+        assert_eq!(
+            vec![
+                Change { location: Location::new(1, 2), old: Square::Empty, new: Square::Snake },
+                Change { location: Location::new(2, 2), old: Square::Empty, new: Square::Snake },
+                Change { location: Location::new(4, 2), old: Square::Empty, new: Square::Fruit(FruitKind::Apple) },
+            ],
+            game.changes().collect::<Vec<_>>()
+        );
+        assert_eq!(0, game.changes().count());
+
+        game.advance();
+
+        assert_eq!(
+            vec![
+                Change { location: Location::new(1, 2), old: Square::Snake, new: Square::Empty },
+                Change { location: Location::new(3, 2), old: Square::Empty, new: Square::Snake },
+            ],
+            game.changes().collect::<Vec<_>>()
+        );
+
+        game.reset_changes();
+        assert_eq!(3, game.changes().count());
+    }
+
+    #[test]
+    fn changes_not_consumed_are_reported_next_time() {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::new(5, 5);
+
+        assert_eq!(1, game.changes().take(1).count());
+        assert_eq!(2, game.changes().count());
+    }
+
+    fn advance_and_collect_events(game: &mut impl Snake) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        game.advance_with_events(&mut |e| events.push(e));
+        events
+    }
+
+    #[test]
+    fn eating_a_fruit_is_reported() {
+        let mut game = create_game();
+
+        assert_eq!(Vec::<GameEvent>::new(), advance_and_collect_events(&mut game));
+        assert_eq!(
+            vec![
+                GameEvent::FruitEaten { at: Location::new(4, 2), kind: FruitKind::Apple },
+                GameEvent::Grew { length: 3 },
+                GameEvent::FruitSpawned { at: Location::new(4, 3) },
+            ],
+            advance_and_collect_events(&mut game)
+        );
+    }
+
+    #[test]
+    fn turning_and_colliding_are_reported() {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::with_boundary_mode(5, 5, BoundaryMode::Solid);
+
+        game.set_direction(Direction::Up);
+        assert_eq!(
+            vec![GameEvent::DirectionChanged { from: Direction::Right, to: Direction::Up }],
+            advance_and_collect_events(&mut game)
+        );
+
+        game.advance();
+        assert_eq!(
+            vec![GameEvent::Collided { at: Location::new(2, -1), with: Collision::Boundary }],
+            advance_and_collect_events(&mut game)
+        );
+        assert_eq!(Vec::<GameEvent>::new(), advance_and_collect_events(&mut game));
+    }
+
+    #[test]
+    fn biting_itself_is_reported() {
+        let mut game = Game::<{3 * 3}, HardcodedNumbersGenerator>::new(3, 3);
+
+        game.set_direction(Direction::Down);
+        game.advance();
+        game.advance();
+
+        assert_eq!(
+            vec![GameEvent::Collided { at: Location::new(1, 1), with: Collision::Snake }],
+            advance_and_collect_events(&mut game)
+        );
+    }
+
+    #[test]
+    fn when_boundary_is_solid_leaving_the_board_loses_the_game() {
+        let mut game: Game<{5 * 5}, HardcodedNumbersGenerator> = GameBuilder::new(5, 5)
+            .boundary_mode(BoundaryMode::Solid)
+            .initial_length(3)
+            .start(Location::new(4, 2))
+            .build();
+
+        assert_board!(
+            game.board(),
+            &board_layout!(
+            "     ",
+            "     ",
+            "F OOO",
+            "     ",
+            "     "
+        )
+        );
+
+        assert_eq!(GameStatus::Lost, game.advance());
+    }
+
+    #[test]
+    fn when_boundary_wraps_horizontally_snake_passes_the_side_edge() {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::with_boundary_mode(5, 5, BoundaryMode::WrapHorizontally);
+
+        game.advance();
+        game.advance();
+        assert_eq!(GameStatus::InProgress, game.advance());
+
+        assert_board!(
+            game.board(),
+            &board_layout!(
+            "     ",
+            "     ",
+            "O  OO",
+            "    F",
+            "     "
+        )
+        );
+    }
+
+    #[test]
+    fn when_boundary_wraps_horizontally_leaving_through_the_top_loses_the_game() {
+        let mut game: Game<{5 * 5}, HardcodedNumbersGenerator> = GameBuilder::new(5, 5)
+            .boundary_mode(BoundaryMode::WrapHorizontally)
+            .direction(Direction::Up)
+            .start(Location::new(2, 0))
+            .build();
+
+        assert_eq!(GameStatus::Lost, game.advance());
+    }
+
+    #[test]
+    fn when_boundary_wraps_vertically_snake_passes_the_top_edge() {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::with_boundary_mode(5, 5, BoundaryMode::WrapVertically);
+
+        game.set_direction(Direction::Up);
+        game.advance();
+        game.advance();
+        assert_eq!(GameStatus::InProgress, game.advance());
+
+        assert_board!(
+            game.board(),
+            &board_layout!(
+            "  O  ",
+            "     ",
+            "    F",
+            "     ",
+            "  O  "
+        )
+        );
+    }
+
+    #[test]
+    fn when_snake_hits_a_wall_the_game_is_lost() {
+        let mut game = create_game_with_walls(&[Location::new(3, 2)]);
+
+        assert_board!(
+            game.board(),
+            &board_layout!(
+            "     ",
+            "     ",
+            " OO#F",
+            "     ",
+            "     "
+        )
+        );
+
+        assert_eq!(GameStatus::Lost, game.advance());
+
+        assert_board!(
+            game.board(),
+            &board_layout!(
+            "     ",
+            "     ",
+            "   # ",
+            "     ",
+            "     "
+        )
+        );
+    }
+
+    #[test]
+    fn fruit_is_never_placed_on_a_wall() {
+        let game = create_game_with_walls(&[Location::new(4, 2), Location::new(4, 3)]);
+
+        assert_board!(
+            game.board(),
+            &board_layout!(
+            "     ",
+            "     ",
+            " OO #",
+            "    #",
+            "    F"
+        )
+        );
+    }
+
+    #[test]
+    fn wall_cannot_be_placed_on_the_snake() {
+        let mut game = Game::<{5 * 5}, HardcodedNumbersGenerator>::new(5, 5);
+
+        assert!(!game.add_wall(Location::new(2, 2)));
+
+        assert_board!(
+            game.board(),
+            &board_layout!(
+            "     ",
+            "     ",
+            " OO F",
+            "     ",
+            "     "
+        )
+        );
+    }
+
     #[test]
     fn place_new_fruit_takes_first_free_location() {
         let expected_location = Location { x: 0, y: 0 };
@@ -804,14 +1796,14 @@ mod tests {
 
         assert_eq!(
             Some(Location { x: 1, y: 0 }),
-            place_new_fruit(expected_location, 2, 2, &taken_locations)
+            place_new_fruit(expected_location, 2, 2, |l| !taken_locations.contains(&l))
         );
 
         let taken_locations = [Location { x: 0, y: 0 }, Location { x: 1, y: 0 }];
 
         assert_eq!(
             Some(Location { x: 0, y: 1 }),
-            place_new_fruit(expected_location, 2, 2, &taken_locations)
+            place_new_fruit(expected_location, 2, 2, |l| !taken_locations.contains(&l))
         );
 
         let expected_location = Location { x: 1, y: 0 };
@@ -819,15 +1811,13 @@ mod tests {
 
         assert_eq!(
             Some(Location { x: 0, y: 0 }),
-            place_new_fruit(expected_location, 2, 2, &taken_locations)
+            place_new_fruit(expected_location, 2, 2, |l| !taken_locations.contains(&l))
         );
 
         let expected_location = Location { x: 4, y: 2 };
-        let taken_locations = [];
-
         assert_eq!(
             Some(Location { x: 1, y: 2 }),
-            place_new_fruit(expected_location, 3, 3, &taken_locations)
+            place_new_fruit(expected_location, 3, 3, |_| true)
         );
     }
 
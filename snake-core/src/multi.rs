@@ -0,0 +1,424 @@
+//! Several snakes sharing one board.
+//!
+//! Every tick all living snakes move at the same time:
+//!
+//! 1. Each snake takes its next queued turn and its head moves one square, crossing the edges
+//!    the boundary mode lets it cross. Leaving through a solid edge kills the snake.
+//! 2. A head entering a square that held a wall or a segment of any snake when the tick started
+//!    kills its snake. Tails count even though they are about to move away and so do heads,
+//!    two snakes moving into each other's heads both die.
+//! 3. Heads of two or more snakes entering the same square kill all of them, a fruit lying
+//!    there stays where it is.
+//! 4. A surviving snake whose head entered the fruit grows by one segment and scores. There are
+//!    only apples, see `FruitKind`.
+//! 5. Dead snakes are removed from the board, then a new fruit is placed if the old one was
+//!    eaten.
+//!
+//! The game is over once at most one snake is left, or once the snake died when there is only
+//! one player. The last snake alive wins, nobody does when the others died in the same tick.
+//! When there is no room left for fruit the longest living snake wins, nobody does on a tie.
+
+use crate::{
+    place_new_fruit, Board, BoundaryMode, Changes, Collision, Direction, FruitKind, GameEvent, GridBoard, Location,
+    RandomNumberGenerator, Square, Storage, TurnQueue,
+};
+#[cfg(feature = "std")]
+use crate::Dynamic;
+use crate::Fixed;
+
+/// Most snakes a game can have, each needs its own `Square::Player`
+pub const MAX_PLAYERS: usize = 16;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MultiStatus {
+    InProgress,
+    Paused,
+    Finished { winner: Option<usize> },
+}
+
+/// One player's snake, a circular buffer of segments like the one in `Game`
+struct Body<S: Storage> {
+    segments: S::Array<Location>,
+    tail: usize,
+    size: usize,
+    direction: Direction,
+    turns: TurnQueue,
+    alive: bool,
+    score: u32,
+}
+
+/// Game of `PLAYERS` snakes with the board and the snakes kept in `S`, see `MultiGame` and
+/// `VecMultiGame`
+///
+/// Player `i` is drawn as `Square::Player(i)`. Snakes start in rows spread evenly over the
+/// board, even players on the left heading right and odd players on the right heading left.
+///
+/// ```rust
+/// use self::snake::{Direction, MultiGame, MultiStatus, Pcg32};
+/// let mut game = MultiGame::<{ 12 * 9 }, Pcg32, 2>::new(12, 9);
+/// game.set_direction(0, Direction::Down);
+/// game.set_direction(1, Direction::Up);
+///
+/// assert_eq!(MultiStatus::InProgress, game.advance());
+/// ```
+pub struct GenericMultiGame<S, R, const PLAYERS: usize>
+where
+    S: Storage,
+    R: RandomNumberGenerator,
+{
+    width: usize,
+    height: usize,
+    boundary_mode: BoundaryMode,
+    snakes: [Body<S>; PLAYERS],
+    initial_length: usize,
+    fruit: Location,
+    fruit_spawns: Option<S::Array<bool>>,
+    status: MultiStatus,
+    ticks: u32,
+    rng: R,
+    pub(crate) board: GridBoard<S>,
+    shown: GridBoard<S>,
+}
+
+/// Multi-snake game on a board of `SIZE` squares, which must be `width * height`
+pub type MultiGame<const SIZE: usize, R, const PLAYERS: usize> = GenericMultiGame<Fixed<SIZE>, R, PLAYERS>;
+/// Multi-snake game on a board sized at runtime, allocated on the heap
+#[cfg(feature = "std")]
+pub type VecMultiGame<R, const PLAYERS: usize> = GenericMultiGame<Dynamic, R, PLAYERS>;
+
+impl<S, R, const PLAYERS: usize> GenericMultiGame<S, R, PLAYERS>
+where
+    S: Storage,
+    R: RandomNumberGenerator,
+{
+    /// Use `GameBuilder::build_multi` to change anything about the start
+    pub fn new(width: usize, height: usize) -> Self {
+        crate::GameBuilder::new(width, height).build_multi()
+    }
+
+    pub(crate) fn empty(
+        width: usize,
+        height: usize,
+        boundary_mode: BoundaryMode,
+        initial_length: usize,
+        rng: R,
+    ) -> Self {
+        GenericMultiGame {
+            width,
+            height,
+            boundary_mode,
+            snakes: core::array::from_fn(|_| Body {
+                segments: S::array(Location::default(), width * height),
+                tail: 0,
+                size: 0,
+                direction: Direction::Right,
+                turns: TurnQueue::default(),
+                alive: true,
+                score: 0,
+            }),
+            initial_length,
+            fruit: Location::new(0, 0),
+            fruit_spawns: None,
+            status: MultiStatus::InProgress,
+            ticks: 0,
+            rng,
+            board: GridBoard::new(width, height),
+            shown: GridBoard::new(width, height),
+        }
+    }
+
+    pub fn board(&self) -> &dyn Board {
+        &self.board
+    }
+
+    /// See `Game::changes`
+    pub fn changes(&mut self) -> Changes<'_> {
+        Changes::new(&self.board, &mut self.shown)
+    }
+
+    /// See `Game::reset_changes`
+    pub fn reset_changes(&mut self) {
+        self.shown = GridBoard::new(self.width, self.height);
+    }
+
+    pub fn advance(&mut self) -> MultiStatus {
+        self.advance_with_events(&mut |_, _| {})
+    }
+
+    /// Advances the game passing everything that happened during the tick to `events` along
+    /// with the player it happened to, `FruitSpawned` belongs to nobody
+    pub fn advance_with_events(&mut self, events: &mut dyn FnMut(Option<usize>, GameEvent)) -> MultiStatus {
+        if self.status != MultiStatus::InProgress {
+            return self.status;
+        }
+        self.ticks += 1;
+
+        let mut heads = [None; PLAYERS];
+        let mut deaths = [None; PLAYERS];
+        for (player, snake) in self.snakes.iter_mut().enumerate().filter(|(_, s)| s.alive) {
+            if let Some(next) = snake.turns.pop() {
+                events(Some(player), GameEvent::DirectionChanged { from: snake.direction, to: next });
+                snake.direction = next;
+            }
+
+            let head = snake.head(self.width * self.height).move_in(snake.direction);
+            match self.boundary_mode.apply(head, self.width, self.height) {
+                Some(head) => heads[player] = Some(head),
+                None => deaths[player] = Some((head, Collision::Boundary)),
+            }
+        }
+
+        for (player, head) in heads.iter().enumerate() {
+            let Some(head) = *head else { continue };
+            let collision = match self.board.at(head) {
+                Square::Wall => Some(Collision::Wall),
+                Square::Snake | Square::Player(_) => Some(Collision::Snake),
+                _ if heads.iter().filter(|other| **other == Some(head)).count() > 1 => Some(Collision::Head),
+                _ => None,
+            };
+            deaths[player] = collision.map(|with| (head, with));
+        }
+
+        let mut fruit_eaten = false;
+        for player in 0..PLAYERS {
+            match (heads[player], deaths[player]) {
+                (Some(head), None) if head == self.fruit => {
+                    self.push_head(player, head);
+                    self.snakes[player].score += 1;
+                    fruit_eaten = true;
+                    events(Some(player), GameEvent::FruitEaten { at: head, kind: FruitKind::Apple });
+                    events(Some(player), GameEvent::Grew { length: self.snakes[player].size });
+                }
+                (Some(head), None) => {
+                    self.pop_tail(player);
+                    self.push_head(player, head);
+                }
+                _ => {}
+            }
+        }
+
+        for (player, death) in deaths.iter().enumerate() {
+            if let Some((at, with)) = *death {
+                events(Some(player), GameEvent::Collided { at, with });
+                self.remove_snake(player);
+            }
+        }
+
+        let alive = self.snakes.iter().filter(|s| s.alive).count();
+        self.status = if alive == 0 || (PLAYERS > 1 && alive == 1) {
+            self.finish((0..PLAYERS).find(|player| self.snakes[*player].alive))
+        } else if fruit_eaten {
+            match self.respawn_fruit() {
+                Some(at) => {
+                    events(None, GameEvent::FruitSpawned { at });
+                    MultiStatus::InProgress
+                }
+                None => self.finish(self.longest()),
+            }
+        } else {
+            MultiStatus::InProgress
+        };
+
+        if let MultiStatus::Finished { winner: Some(winner) } = self.status {
+            events(Some(winner), GameEvent::Won);
+        }
+        self.status
+    }
+
+    /// Queues a turn for `player`, ignored while paused or once the snake is dead
+    pub fn set_direction(&mut self, player: usize, direction: Direction) {
+        let snake = &mut self.snakes[player];
+        if self.status != MultiStatus::Paused && snake.alive {
+            snake.turns.push(direction, snake.direction);
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.status == MultiStatus::InProgress {
+            self.status = MultiStatus::Paused;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.status == MultiStatus::Paused {
+            self.status = MultiStatus::InProgress;
+        }
+    }
+
+    /// Puts every snake back where it started, the walls stay and fruit is placed by
+    /// a generator seeded from the current one
+    pub fn restart(&mut self) {
+        let seed = (u64::from(self.rng.next()) << 32) | u64::from(self.rng.next());
+        self.rng = R::from_seed(seed);
+
+        for square in self.board.data.as_mut() {
+            if matches!(*square, Square::Player(_) | Square::Fruit(_)) {
+                *square = Square::Empty;
+            }
+        }
+        for snake in &mut self.snakes {
+            snake.tail = 0;
+            snake.size = 0;
+            snake.turns = TurnQueue::default();
+            snake.alive = true;
+            snake.score = 0;
+        }
+        self.ticks = 0;
+
+        self.place_snakes().expect("snakes fit where they were placed before");
+        self.place_first_fruit();
+    }
+
+    pub fn status(&self) -> MultiStatus {
+        self.status
+    }
+
+    /// Number of ticks played while the game was in progress
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// Current fruit, there is none once the game is over
+    pub fn fruit(&self) -> Option<Location> {
+        match self.status {
+            MultiStatus::Finished { .. } => None,
+            _ => Some(self.fruit),
+        }
+    }
+
+    pub fn is_alive(&self, player: usize) -> bool {
+        self.snakes[player].alive
+    }
+
+    /// Number of fruits `player` ate
+    pub fn score(&self, player: usize) -> u32 {
+        self.snakes[player].score
+    }
+
+    /// Length of `player`'s snake, it is kept after the snake died
+    pub fn length(&self, player: usize) -> usize {
+        self.snakes[player].size
+    }
+
+    pub fn head(&self, player: usize) -> Location {
+        self.snakes[player].head(self.width * self.height)
+    }
+
+    pub fn direction(&self, player: usize) -> Direction {
+        self.snakes[player].direction
+    }
+
+    /// Where `player`'s head starts and the direction it heads in
+    fn start(&self, player: usize) -> (Location, Direction) {
+        let y = ((player + 1) * self.height / (PLAYERS + 1)) as i32;
+        let margin = (self.width / 4) as i32;
+        let body = self.initial_length.saturating_sub(1) as i32;
+        if player.is_multiple_of(2) {
+            (Location::new(margin + body, y), Direction::Right)
+        } else {
+            (Location::new(self.width as i32 - 1 - margin - body, y), Direction::Left)
+        }
+    }
+
+    /// Lays out every snake straight behind its starting head
+    pub(crate) fn place_snakes(&mut self) -> Result<(), crate::BuildError> {
+        for player in 0..PLAYERS {
+            let (head, direction) = self.start(player);
+            self.snakes[player].direction = direction;
+
+            let mut segment = (1..self.initial_length).fold(head, |l, _| l.move_in(direction.opposite()));
+            for _ in 0..self.initial_length {
+                if !segment.is_within(self.width, self.height) {
+                    return Err(crate::BuildError::OffBoard { at: segment });
+                }
+                if self.board.at(segment) != Square::Empty {
+                    return Err(crate::BuildError::Obstacle { at: segment });
+                }
+                self.push_head(player, segment);
+                segment = segment.move_in(direction);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn add_fruit_spawn(&mut self, location: Location) {
+        let index = location.y as usize * self.width + location.x as usize;
+        let size = self.width * self.height;
+        self.fruit_spawns.get_or_insert_with(|| S::array(false, size))[index] = true;
+    }
+
+    /// Places the first fruit, which can be impossible on a board full of snakes
+    pub(crate) fn place_first_fruit(&mut self) {
+        self.status = match self.respawn_fruit() {
+            Some(_) => MultiStatus::InProgress,
+            None => self.finish(self.longest()),
+        };
+    }
+
+    fn respawn_fruit(&mut self) -> Option<Location> {
+        let (width, height) = (self.width, self.height);
+        let fruit = Location::new(self.rng.next() as i32, self.rng.next() as i32).wrap(width, height);
+        let is_free = |l: Location| self.board.at(l) == Square::Empty;
+
+        let fruit = self
+            .fruit_spawns
+            .as_ref()
+            .and_then(|spawns| {
+                place_new_fruit(fruit, width, height, |l| spawns[l.y as usize * width + l.x as usize] && is_free(l))
+            })
+            .or_else(|| place_new_fruit(fruit, width, height, is_free))?;
+
+        self.fruit = fruit;
+        *self.board.at_mut(&fruit) = Square::Fruit(FruitKind::Apple);
+        Some(fruit)
+    }
+
+    fn finish(&mut self, winner: Option<usize>) -> MultiStatus {
+        if self.board.at(self.fruit) == Square::Fruit(FruitKind::Apple) {
+            *self.board.at_mut(&self.fruit) = Square::Empty;
+        }
+        MultiStatus::Finished { winner }
+    }
+
+    /// The living snake longer than all the others
+    fn longest(&self) -> Option<usize> {
+        let alive = || (0..PLAYERS).filter(|player| self.snakes[*player].alive);
+        let longest = alive().map(|player| self.snakes[player].size).max()?;
+        let mut with_longest = alive().filter(|player| self.snakes[*player].size == longest);
+        match (with_longest.next(), with_longest.next()) {
+            (Some(player), None) => Some(player),
+            _ => None,
+        }
+    }
+
+    fn push_head(&mut self, player: usize, location: Location) {
+        let size = self.width * self.height;
+        let snake = &mut self.snakes[player];
+        snake.segments[(snake.tail + snake.size) % size] = location;
+        snake.size += 1;
+        *self.board.at_mut(&location) = Square::Player(player as u8);
+    }
+
+    fn pop_tail(&mut self, player: usize) {
+        let size = self.width * self.height;
+        let snake = &mut self.snakes[player];
+        *self.board.at_mut(&snake.segments[snake.tail]) = Square::Empty;
+        snake.tail = (snake.tail + 1) % size;
+        snake.size -= 1;
+    }
+
+    fn remove_snake(&mut self, player: usize) {
+        let size = self.width * self.height;
+        let snake = &mut self.snakes[player];
+        for i in 0..snake.size {
+            *self.board.at_mut(&snake.segments[(snake.tail + i) % size]) = Square::Empty;
+        }
+        snake.alive = false;
+    }
+}
+
+impl<S: Storage> Body<S> {
+    fn head(&self, board_size: usize) -> Location {
+        self.segments[(self.tail + self.size - 1) % board_size]
+    }
+}
@@ -0,0 +1,675 @@
+//! Recording games and playing them back.
+//!
+//! A game only depends on the random number generator and the `set_direction` calls, so a seed,
+//! the board configuration and the direction changes with the ticks they happened at are enough
+//! to play it again. The final state is stored as well, so playback can tell whether the engine
+//! still behaves the same.
+//!
+//! The format, integers are LEB128 varints unless stated otherwise:
+//!
+//! * `SNKR` magic and a version byte
+//! * how the game starts, a byte telling which of these follows:
+//!   * `0` - width, height, boundary mode byte, seed as little endian `u64`, length of the level
+//!     text and the text, an empty level means the default layout, then the fruit policy
+//!   * `1` - length of a snapshot and the snapshot the game was resumed from
+//! * length of the inputs and the inputs, each one is `(ticks since the previous input << 3) | code`
+//!   where the code is a direction byte or `4` for pause, `5` for resume and `6` for restart,
+//!   ticks start from 0 again after a restart
+//! * ticks, status byte, score, length and a little endian `u32` checksum of the final board
+
+use core::fmt;
+
+use crate::encoding::{self, BufferFull, Reader, Writer};
+use crate::{
+    Board, BoundaryMode, Changes, Direction, FruitPolicy, GameBuilder, GameEvent, GameStatus, GenericGame, Level,
+    LevelError, Location, RandomNumberGenerator, Snake, SnapshotError, Storage,
+};
+
+const MAGIC: &[u8; 4] = b"SNKR";
+const VERSION: u8 = 1;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ReplayError {
+    /// The output buffer or the recorder's input log is too small
+    BufferFull,
+    /// The data is truncated or contains invalid values
+    Malformed,
+    UnsupportedVersion(u8),
+    /// The level stored in the replay can't be parsed
+    Level(LevelError),
+    /// The snapshot the replay starts from can't be restored
+    Snapshot(SnapshotError),
+    /// The replay was recorded on a board of a different size
+    SizeMismatch { width: usize, height: usize },
+    /// Playback ended in a different state than the recording
+    Diverged,
+}
+
+impl From<SnapshotError> for ReplayError {
+    fn from(error: SnapshotError) -> Self {
+        match error {
+            SnapshotError::SizeMismatch { width, height } => ReplayError::SizeMismatch { width, height },
+            error => ReplayError::Snapshot(error),
+        }
+    }
+}
+
+impl From<BufferFull> for ReplayError {
+    fn from(_: BufferFull) -> Self {
+        ReplayError::BufferFull
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::BufferFull => write!(f, "replay does not fit in the buffer"),
+            ReplayError::Malformed => write!(f, "replay is malformed"),
+            ReplayError::UnsupportedVersion(v) => write!(f, "replay version {} is not supported", v),
+            ReplayError::Level(e) => write!(f, "replay level is invalid: {}", e),
+            ReplayError::Snapshot(e) => write!(f, "replay start is invalid: {}", e),
+            ReplayError::SizeMismatch { width, height } => {
+                write!(f, "replay was recorded on a {}x{} board", width, height)
+            }
+            ReplayError::Diverged => write!(f, "playback did not end in the recorded state"),
+        }
+    }
+}
+
+/// How the recorded game was set up
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ReplayStart<'a> {
+    New {
+        width: usize,
+        height: usize,
+        boundary_mode: BoundaryMode,
+        seed: u64,
+        /// Text of the level the game was played on, `None` for the default layout
+        level: Option<&'a str>,
+        fruit_policy: FruitPolicy,
+    },
+    /// The game was resumed from this snapshot
+    Snapshot(&'a [u8]),
+}
+
+/// A call on the recorded game
+#[derive(PartialEq, Copy, Clone, Debug)]
+enum Input {
+    Turn(Direction),
+    Pause,
+    Resume,
+    Restart,
+}
+
+impl Input {
+    fn code(self) -> u8 {
+        match self {
+            Input::Turn(direction) => encoding::direction_to_u8(direction),
+            Input::Pause => 4,
+            Input::Resume => 5,
+            Input::Restart => 6,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Input> {
+        match code {
+            4 => Some(Input::Pause),
+            5 => Some(Input::Resume),
+            6 => Some(Input::Restart),
+            code => encoding::direction_from_u8(code).map(Input::Turn),
+        }
+    }
+
+    fn apply(self, game: &mut dyn Snake) {
+        match self {
+            Input::Turn(direction) => game.set_direction(direction),
+            Input::Pause => game.pause(),
+            Input::Resume => game.resume(),
+            Input::Restart => game.restart(),
+        }
+    }
+}
+
+/// The state compared at the end of playback
+#[derive(PartialEq, Copy, Clone, Debug)]
+struct Outcome {
+    ticks: u32,
+    status: GameStatus,
+    score: u32,
+    length: usize,
+    checksum: u32,
+}
+
+impl Outcome {
+    fn of(game: &dyn Snake) -> Outcome {
+        Outcome {
+            ticks: game.ticks(),
+            status: game.status(),
+            score: game.score(),
+            length: game.length(),
+            checksum: board_checksum(game.board()),
+        }
+    }
+}
+
+fn board_checksum(board: &dyn Board) -> u32 {
+    encoding::checksum(board.iter().map(|(_, square)| encoding::square_to_u8(square)))
+}
+
+/// Where a `Recorder` keeps the inputs it logged
+pub trait InputLog: Default {
+    /// Appends `bytes` to the log, false when there's no room for them
+    fn append(&mut self, bytes: &[u8]) -> bool;
+    fn as_bytes(&self) -> &[u8];
+}
+
+/// Up to `CAPACITY` bytes of inputs, nothing is allocated
+pub struct FixedLog<const CAPACITY: usize> {
+    bytes: [u8; CAPACITY],
+    size: usize,
+}
+
+impl<const CAPACITY: usize> Default for FixedLog<CAPACITY> {
+    fn default() -> Self {
+        FixedLog {
+            bytes: [0; CAPACITY],
+            size: 0,
+        }
+    }
+}
+
+impl<const CAPACITY: usize> InputLog for FixedLog<CAPACITY> {
+    fn append(&mut self, bytes: &[u8]) -> bool {
+        let Some(free) = self.bytes.get_mut(self.size..self.size + bytes.len()) else {
+            return false;
+        };
+        free.copy_from_slice(bytes);
+        self.size += bytes.len();
+        true
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.size]
+    }
+}
+
+/// Grows as long as the game goes on
+#[cfg(feature = "std")]
+impl InputLog for Vec<u8> {
+    fn append(&mut self, bytes: &[u8]) -> bool {
+        self.extend_from_slice(bytes);
+        true
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Plays the game like the wrapped `Game` and records every input, pause, resume and restart
+/// into `L`, a `FixedLog` or on std a `Vec<u8>`
+///
+/// ```rust
+/// use self::snake::{BoundaryMode, Direction, Fixed, FixedLog, Game, Pcg32, Recorder, Replay, Snake};
+/// let mut recorder = Recorder::<Fixed<{ 8 * 8 }>, Pcg32, FixedLog<64>>::new(8, 8, BoundaryMode::Wrap, 42);
+/// recorder.advance();
+/// recorder.set_direction(Direction::Up);
+/// recorder.advance();
+///
+/// let mut buffer = [0; 128];
+/// let size = recorder.write(&mut buffer).unwrap();
+///
+/// let replay = Replay::parse(&buffer[..size]).unwrap();
+/// let game: Game<{ 8 * 8 }, Pcg32> = replay.play().unwrap();
+/// assert_eq!(recorder.head(), game.head());
+/// ```
+pub struct Recorder<'a, S, R, L>
+where
+    S: Storage,
+    R: RandomNumberGenerator,
+    L: InputLog,
+{
+    game: GenericGame<S, R>,
+    start: ReplayStart<'a>,
+    inputs: L,
+    last_input_tick: u32,
+    overflowed: bool,
+}
+
+impl<'a, S, R, L> Recorder<'a, S, R, L>
+where
+    S: Storage,
+    R: RandomNumberGenerator,
+    L: InputLog,
+{
+    pub fn new(width: usize, height: usize, boundary_mode: BoundaryMode, seed: u64) -> Self {
+        let game = GenericGame::with_rng(width, height, boundary_mode, R::from_seed(seed));
+        Self::recording(
+            game,
+            ReplayStart::New {
+                width,
+                height,
+                boundary_mode,
+                seed,
+                level: None,
+                fruit_policy: FruitPolicy::new(),
+            },
+        )
+    }
+
+    /// Starts the game the way `start` describes, the only way to record extra fruits
+    ///
+    /// ```rust
+    /// use self::snake::{BoundaryMode, Fixed, FruitPolicy, Game, Pcg32, Recorder, Replay, ReplayStart};
+    /// let start = ReplayStart::New {
+    ///     width: 8,
+    ///     height: 8,
+    ///     boundary_mode: BoundaryMode::Wrap,
+    ///     seed: 5,
+    ///     level: None,
+    ///     fruit_policy: FruitPolicy::mixed(),
+    /// };
+    /// let recorder = Recorder::<Fixed<{ 8 * 8 }>, Pcg32, Vec<u8>>::from_start(start).unwrap();
+    /// let game: Game<{ 8 * 8 }, Pcg32> = Replay::parse(&recorder.to_bytes().unwrap())
+    ///     .unwrap()
+    ///     .play()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(FruitPolicy::mixed(), game.fruit_policy());
+    /// ```
+    pub fn from_start(start: ReplayStart<'a>) -> Result<Self, ReplayError> {
+        Ok(Self::recording(start.game()?, start))
+    }
+
+    pub fn from_level(level: &Level<'a>, boundary_mode: BoundaryMode, seed: u64) -> Self {
+        let game = GenericGame::from_level_with_rng(level, boundary_mode, R::from_seed(seed));
+        Self::recording(
+            game,
+            ReplayStart::New {
+                width: level.width(),
+                height: level.height(),
+                boundary_mode,
+                seed,
+                level: Some(level.text()),
+                fruit_policy: FruitPolicy::new(),
+            },
+        )
+    }
+
+    /// Continues a game saved with `Game::snapshot`
+    pub fn from_snapshot(snapshot: &'a [u8]) -> Result<Self, SnapshotError> {
+        let game = GenericGame::restore(snapshot)?;
+        Ok(Self::recording(game, ReplayStart::Snapshot(snapshot)))
+    }
+
+    fn recording(game: GenericGame<S, R>, start: ReplayStart<'a>) -> Self {
+        Recorder {
+            last_input_tick: game.ticks(),
+            game,
+            start,
+            inputs: L::default(),
+            overflowed: false,
+        }
+    }
+
+    pub fn game(&self) -> &GenericGame<S, R> {
+        &self.game
+    }
+
+    /// See `Game::changes`
+    pub fn changes(&mut self) -> Changes<'_> {
+        self.game.changes()
+    }
+
+    /// See `Game::reset_changes`
+    pub fn reset_changes(&mut self) {
+        self.game.reset_changes()
+    }
+
+    /// Writes the replay of the game so far into `out` and returns the number of bytes used
+    ///
+    /// Fails with `BufferFull` when `out` is too small or when a `FixedLog` ran out of space.
+    pub fn write(&self, out: &mut [u8]) -> Result<usize, ReplayError> {
+        if self.overflowed {
+            return Err(ReplayError::BufferFull);
+        }
+
+        let mut writer = Writer::new(out);
+        writer.bytes(MAGIC)?;
+        writer.u8(VERSION)?;
+
+        match self.start {
+            ReplayStart::New {
+                width,
+                height,
+                boundary_mode,
+                seed,
+                level,
+                fruit_policy,
+            } => {
+                let level = level.unwrap_or("");
+                writer.u8(0)?;
+                writer.varint(width as u64)?;
+                writer.varint(height as u64)?;
+                writer.u8(encoding::boundary_mode_to_u8(boundary_mode))?;
+                writer.u64_le(seed)?;
+                writer.varint(level.len() as u64)?;
+                writer.bytes(level.as_bytes())?;
+                encoding::write_fruit_policy(&mut writer, &fruit_policy)?;
+            }
+            ReplayStart::Snapshot(snapshot) => {
+                writer.u8(1)?;
+                writer.varint(snapshot.len() as u64)?;
+                writer.bytes(snapshot)?;
+            }
+        }
+
+        let inputs = self.inputs.as_bytes();
+        writer.varint(inputs.len() as u64)?;
+        writer.bytes(inputs)?;
+
+        let outcome = Outcome::of(&self.game);
+        writer.varint(u64::from(outcome.ticks))?;
+        writer.u8(encoding::status_to_u8(outcome.status))?;
+        writer.varint(u64::from(outcome.score))?;
+        writer.varint(outcome.length as u64)?;
+        writer.u32_le(outcome.checksum)?;
+
+        Ok(writer.position())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ReplayError> {
+        // Magic, version, seed, fruit policy, checksum and the longest possible varints
+        const OVERHEAD: usize = 160;
+
+        let start = match self.start {
+            ReplayStart::New { level, .. } => level.map_or(0, str::len),
+            ReplayStart::Snapshot(snapshot) => snapshot.len(),
+        };
+        let mut bytes = vec![0; OVERHEAD + start + self.inputs.as_bytes().len()];
+        let size = self.write(&mut bytes)?;
+        bytes.truncate(size);
+        Ok(bytes)
+    }
+
+    /// Applies `input` to the game and logs it
+    fn record(&mut self, input: Input) {
+        if !self.overflowed {
+            self.log(input);
+        }
+        input.apply(&mut self.game);
+        if input == Input::Restart {
+            self.last_input_tick = 0;
+        }
+    }
+
+    fn log(&mut self, input: Input) {
+        let tick = self.game.ticks();
+        let input = (u64::from(tick - self.last_input_tick) << 3) | u64::from(input.code());
+
+        let mut bytes = [0; 10];
+        let mut writer = Writer::new(&mut bytes);
+        writer.varint(input).expect("a varint fits in 10 bytes");
+        let size = writer.position();
+        if self.inputs.append(&bytes[..size]) {
+            self.last_input_tick = tick;
+        } else {
+            self.overflowed = true;
+        }
+    }
+}
+
+impl<'a, S, R, L> Snake for Recorder<'a, S, R, L>
+where
+    S: Storage,
+    R: RandomNumberGenerator,
+    L: InputLog,
+{
+    fn board(&self) -> &dyn Board {
+        self.game.board()
+    }
+
+    fn advance_with_events(&mut self, events: &mut dyn FnMut(GameEvent)) -> GameStatus {
+        self.game.advance_with_events(events)
+    }
+
+    fn set_direction(&mut self, direction: Direction) {
+        self.record(Input::Turn(direction));
+    }
+
+    fn pause(&mut self) {
+        self.record(Input::Pause);
+    }
+
+    fn resume(&mut self) {
+        self.record(Input::Resume);
+    }
+
+    fn restart(&mut self) {
+        self.record(Input::Restart);
+    }
+
+    fn score(&self) -> u32 {
+        self.game.score()
+    }
+
+    fn length(&self) -> usize {
+        self.game.length()
+    }
+
+    fn segment(&self, index: usize) -> Option<Location> {
+        self.game.segment(index)
+    }
+
+    fn head(&self) -> Location {
+        self.game.head()
+    }
+
+    fn direction(&self) -> Direction {
+        self.game.direction()
+    }
+
+    fn pending_direction(&self) -> Direction {
+        self.game.pending_direction()
+    }
+
+    fn status(&self) -> GameStatus {
+        self.game.status()
+    }
+
+    fn ticks(&self) -> u32 {
+        self.game.ticks()
+    }
+
+    fn fruit(&self) -> Option<Location> {
+        self.game.fruit()
+    }
+
+    fn boundary_mode(&self) -> BoundaryMode {
+        self.game.boundary_mode()
+    }
+}
+
+/// A parsed replay, borrowing the bytes it was read from
+#[derive(Clone, Debug)]
+pub struct Replay<'a> {
+    pub start: ReplayStart<'a>,
+    inputs: &'a [u8],
+    outcome: Outcome,
+}
+
+impl<'a> Replay<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Replay<'a>, ReplayError> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.bytes(MAGIC.len()) != Some(&MAGIC[..]) {
+            return Err(ReplayError::Malformed);
+        }
+        match reader.u8() {
+            Some(VERSION) => {}
+            Some(version) => return Err(ReplayError::UnsupportedVersion(version)),
+            None => return Err(ReplayError::Malformed),
+        }
+
+        let replay = Self::parse_body(&mut reader).ok_or(ReplayError::Malformed)?;
+
+        if !reader.is_empty() {
+            return Err(ReplayError::Malformed);
+        }
+
+        Ok(replay)
+    }
+
+    fn parse_body(reader: &mut Reader<'a>) -> Option<Replay<'a>> {
+        let start = match reader.u8()? {
+            0 => Self::parse_new_game(reader)?,
+            1 => {
+                let size = usize::try_from(reader.varint()?).ok()?;
+                ReplayStart::Snapshot(reader.bytes(size)?)
+            }
+            _ => return None,
+        };
+
+        let inputs_size = usize::try_from(reader.varint()?).ok()?;
+        let inputs = reader.bytes(inputs_size)?;
+
+        let outcome = Outcome {
+            ticks: u32::try_from(reader.varint()?).ok()?,
+            status: encoding::status_from_u8(reader.u8()?)?,
+            score: u32::try_from(reader.varint()?).ok()?,
+            length: usize::try_from(reader.varint()?).ok()?,
+            checksum: reader.u32_le()?,
+        };
+
+        Some(Replay { start, inputs, outcome })
+    }
+
+    fn parse_new_game(reader: &mut Reader<'a>) -> Option<ReplayStart<'a>> {
+        let width = usize::try_from(reader.varint()?).ok()?;
+        let height = usize::try_from(reader.varint()?).ok()?;
+        let boundary_mode = encoding::boundary_mode_from_u8(reader.u8()?)?;
+        let seed = reader.u64_le()?;
+
+        let level_size = usize::try_from(reader.varint()?).ok()?;
+        let level = core::str::from_utf8(reader.bytes(level_size)?).ok()?;
+        let fruit_policy = encoding::read_fruit_policy(reader)?;
+
+        Some(ReplayStart::New {
+            width,
+            height,
+            boundary_mode,
+            seed,
+            level: if level.is_empty() { None } else { Some(level) },
+            fruit_policy,
+        })
+    }
+
+    /// Plays the replay again and checks that it ends the same way it was recorded
+    pub fn play<S, R>(&self) -> Result<GenericGame<S, R>, ReplayError>
+    where
+        S: Storage,
+        R: RandomNumberGenerator,
+    {
+        let mut game: GenericGame<S, R> = self.start.game()?;
+
+        let inputs = Inputs {
+            reader: Reader::new(self.inputs),
+            tick: game.ticks(),
+        };
+
+        for input in inputs {
+            let (tick, input) = input?;
+            advance_until(&mut game, tick);
+            input.apply(&mut game);
+        }
+
+        advance_until(&mut game, self.outcome.ticks);
+
+        if Outcome::of(&game) != self.outcome {
+            return Err(ReplayError::Diverged);
+        }
+
+        Ok(game)
+    }
+}
+
+impl<'a> ReplayStart<'a> {
+    /// Creates the game as it was when the recording started
+    fn game<S, R>(&self) -> Result<GenericGame<S, R>, ReplayError>
+    where
+        S: Storage,
+        R: RandomNumberGenerator,
+    {
+        match *self {
+            ReplayStart::New {
+                width,
+                height,
+                boundary_mode,
+                seed,
+                level,
+                fruit_policy,
+            } => {
+                if !width.checked_mul(height).is_some_and(S::fits) {
+                    return Err(ReplayError::SizeMismatch { width, height });
+                }
+
+                let builder = GameBuilder::new(width, height)
+                    .boundary_mode(boundary_mode)
+                    .seed(seed)
+                    .fruit_policy(fruit_policy);
+                match level {
+                    None => Ok(builder.build()),
+                    Some(text) => {
+                        let level = Level::parse(text).map_err(ReplayError::Level)?;
+                        if (level.width(), level.height()) != (width, height) {
+                            return Err(ReplayError::Malformed);
+                        }
+                        Ok(builder.level(&level).build())
+                    }
+                }
+            }
+            ReplayStart::Snapshot(snapshot) => Ok(GenericGame::restore(snapshot)?),
+        }
+    }
+}
+
+/// Decodes the inputs into the ticks they happened at and what happened
+struct Inputs<'a> {
+    reader: Reader<'a>,
+    tick: u32,
+}
+
+impl<'a> Iterator for Inputs<'a> {
+    type Item = Result<(u32, Input), ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reader.is_empty() {
+            return None;
+        }
+
+        let value = self.reader.varint();
+        let input = value.and_then(|value| Input::from_code((value & 0b111) as u8));
+        let tick = value
+            .and_then(|value| u32::try_from(value >> 3).ok())
+            .and_then(|delta| self.tick.checked_add(delta));
+
+        match (tick, input) {
+            (Some(tick), Some(input)) => {
+                self.tick = if input == Input::Restart { 0 } else { tick };
+                Some(Ok((tick, input)))
+            }
+            _ => {
+                // Nothing after a malformed input can be trusted
+                self.reader = Reader::new(&[]);
+                Some(Err(ReplayError::Malformed))
+            }
+        }
+    }
+}
+
+fn advance_until(game: &mut dyn Snake, tick: u32) {
+    while game.ticks() < tick && game.status() == GameStatus::InProgress {
+        game.advance();
+    }
+}
@@ -0,0 +1,65 @@
+//! Built-in random number generator.
+
+use crate::RandomNumberGenerator;
+
+const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+const DEFAULT_STREAM: u64 = 0xda3e_39cb_94b9_5bdb;
+
+/// PCG-XSH-RR with 64 bits of state, see <https://www.pcg-random.org>
+///
+/// Small, fast and statistically good, and the numbers only depend on the seed so they are the
+/// same on every platform.
+///
+/// ```rust
+/// use self::snake::{Pcg32, RandomNumberGenerator};
+/// let mut a = Pcg32::from_seed(7);
+/// let mut b = Pcg32::from_seed(7);
+/// assert_eq!(a.next(), b.next());
+/// ```
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    /// Generators with the same `state` but different `stream` give different sequences
+    pub fn new(state: u64, stream: u64) -> Pcg32 {
+        let mut rng = Pcg32 {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.step();
+        rng.state = rng.state.wrapping_add(state);
+        rng.step();
+        rng
+    }
+
+    fn step(&mut self) {
+        self.state = self.state.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+    }
+}
+
+impl RandomNumberGenerator for Pcg32 {
+    fn from_seed(seed: u64) -> Self {
+        Pcg32::new(seed, DEFAULT_STREAM)
+    }
+
+    fn next(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    fn state(&self) -> u128 {
+        (u128::from(self.state) << 64) | u128::from(self.increment)
+    }
+
+    fn from_state(state: u128) -> Self {
+        Pcg32 {
+            state: (state >> 64) as u64,
+            increment: state as u64 | 1,
+        }
+    }
+}
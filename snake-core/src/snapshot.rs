@@ -0,0 +1,552 @@
+//! Saving a running game and restoring it later.
+//!
+//! The binary format, integers are LEB128 varints unless stated otherwise and locations are
+//! stored as `y * width + x`:
+//!
+//! * `SNKS` magic and a version byte
+//! * width, height, boundary mode byte
+//! * direction byte, number of queued turns and their direction bytes
+//! * status byte, score, ticks, apple location plus one or 0 when there's no apple
+//! * generator state as two little endian `u64`, the low half first
+//! * snake length and its segments from the tail to the head
+//! * the board as runs of a square byte followed by the run length
+//! * number of fruit spawn points and their locations, none means fruit can go anywhere
+//! * the direction byte, length and segments of the snake `restart` goes back to
+//! * the fruit policy, the segments the snake is still to grow by, the number of extra fruits
+//!   and for each its location, kind byte and the tick it expires at plus one or 0 when it
+//!   doesn't
+//!
+//! Squares already drawn are not part of a snapshot, `changes` of a restored game starts from
+//! an empty board.
+
+use core::fmt;
+
+use crate::encoding::{self, BufferFull, Reader, Writer};
+use crate::{
+    Board, BoundaryMode, Direction, Fruit, FruitKind, GameStatus, GenericGame, Location, RandomNumberGenerator,
+    Square, Storage, TurnQueue, MAX_EXTRA_FRUITS,
+};
+
+const MAGIC: &[u8; 4] = b"SNKS";
+const VERSION: u8 = 1;
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SnapshotError {
+    /// The output buffer is too small
+    BufferFull,
+    /// The data is truncated or describes an impossible game
+    Malformed,
+    UnsupportedVersion(u8),
+    /// The snapshot was taken on a board of a different size
+    SizeMismatch { width: usize, height: usize },
+}
+
+impl From<BufferFull> for SnapshotError {
+    fn from(_: BufferFull) -> Self {
+        SnapshotError::BufferFull
+    }
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::BufferFull => write!(f, "snapshot does not fit in the buffer"),
+            SnapshotError::Malformed => write!(f, "snapshot is malformed"),
+            SnapshotError::UnsupportedVersion(v) => write!(f, "snapshot version {} is not supported", v),
+            SnapshotError::SizeMismatch { width, height } => {
+                write!(f, "snapshot was taken on a {}x{} board", width, height)
+            }
+        }
+    }
+}
+
+/// Everything in a snapshot except the snake, the board and the fruit spawns
+struct Header {
+    width: usize,
+    height: usize,
+    boundary_mode: BoundaryMode,
+    direction: Direction,
+    turns: TurnQueue,
+    status: GameStatus,
+    score: u32,
+    ticks: u32,
+    fruit: Option<Location>,
+    rng: u128,
+}
+
+impl<S, R> GenericGame<S, R>
+where
+    S: Storage,
+    R: RandomNumberGenerator,
+{
+    /// Writes the complete state of the game into `out` and returns the number of bytes used
+    ///
+    /// ```rust
+    /// use self::snake::{Game, Pcg32, Snake};
+    /// let mut game = Game::<{ 8 * 8 }, Pcg32>::new(8, 8);
+    /// game.advance();
+    ///
+    /// let mut buffer = [0; 128];
+    /// let size = game.snapshot(&mut buffer).unwrap();
+    /// let mut restored = Game::<{ 8 * 8 }, Pcg32>::restore(&buffer[..size]).unwrap();
+    ///
+    /// assert_eq!(game.head(), restored.head());
+    /// assert_eq!(game.advance(), restored.advance());
+    /// ```
+    pub fn snapshot(&self, out: &mut [u8]) -> Result<usize, SnapshotError> {
+        let mut writer = Writer::new(out);
+        writer.bytes(MAGIC)?;
+        writer.u8(VERSION)?;
+        writer.varint(self.width as u64)?;
+        writer.varint(self.height as u64)?;
+        writer.u8(encoding::boundary_mode_to_u8(self.boundary_mode))?;
+        writer.u8(encoding::direction_to_u8(self.current_direction))?;
+        writer.varint(self.turns.as_slice().len() as u64)?;
+        for turn in self.turns.as_slice() {
+            writer.u8(encoding::direction_to_u8(*turn))?;
+        }
+        writer.u8(encoding::status_to_u8(self.status))?;
+        writer.varint(u64::from(self.score))?;
+        writer.varint(u64::from(self.ticks))?;
+        writer.varint(self.fruit.map_or(0, |fruit| self.index(fruit) as u64 + 1))?;
+
+        let rng = self.rng.state();
+        writer.u64_le(rng as u64)?;
+        writer.u64_le((rng >> 64) as u64)?;
+
+        writer.varint(self.snake_size as u64)?;
+        for i in 0..self.snake_size {
+            writer.varint(self.index(self.snake[(self.snake_tail + i) % self.size()]) as u64)?;
+        }
+
+        let mut squares = self.board.iter().map(|(_, square)| square).peekable();
+        while let Some(square) = squares.next() {
+            let mut run = 1;
+            while squares.next_if_eq(&square).is_some() {
+                run += 1;
+            }
+            writer.u8(encoding::square_to_u8(square))?;
+            writer.varint(run)?;
+        }
+
+        let spawns = self.fruit_spawns.as_ref().map_or(&[][..], |s| s.as_ref());
+        writer.varint(spawns.iter().filter(|s| **s).count() as u64)?;
+        for (index, _) in spawns.iter().enumerate().filter(|(_, s)| **s) {
+            writer.varint(index as u64)?;
+        }
+
+        writer.u8(encoding::direction_to_u8(self.initial_direction))?;
+        writer.varint(self.initial_length as u64)?;
+        for i in 0..self.initial_length {
+            writer.varint(self.index(self.initial_snake[i]) as u64)?;
+        }
+
+        encoding::write_fruit_policy(&mut writer, &self.fruit_policy)?;
+        writer.varint(self.pending_growth as u64)?;
+        writer.varint(self.extra_fruits.iter().flatten().count() as u64)?;
+        for fruit in self.extra_fruits.iter().flatten() {
+            writer.varint(self.index(fruit.at) as u64)?;
+            writer.u8(encoding::fruit_kind_to_u8(fruit.kind))?;
+            writer.varint(fruit.expires.map_or(0, |tick| u64::from(tick) + 1))?;
+        }
+
+        Ok(writer.position())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        // Header, fruit policy and extra fruits, then for every square a segment, an initial
+        // segment and a spawn point of up to 4 bytes each and up to 2 bytes of runs, which holds
+        // for boards of less than 2^28 squares
+        let mut bytes = vec![0; 192 + 16 * self.size()];
+        let size = self.snapshot(&mut bytes).expect("buffer is big enough for any game");
+        bytes.truncate(size);
+        bytes
+    }
+
+    /// Recreates a game saved with `snapshot`
+    pub fn restore(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.bytes(MAGIC.len()) != Some(&MAGIC[..]) {
+            return Err(SnapshotError::Malformed);
+        }
+        match reader.u8() {
+            Some(VERSION) => {}
+            Some(version) => return Err(SnapshotError::UnsupportedVersion(version)),
+            None => return Err(SnapshotError::Malformed),
+        }
+
+        let width = varint_usize(&mut reader)?;
+        let height = varint_usize(&mut reader)?;
+        let size = match width.checked_mul(height) {
+            Some(size) if S::fits(size) => size,
+            _ => return Err(SnapshotError::SizeMismatch { width, height }),
+        };
+
+        let header = Self::read_header(&mut reader, width, height).ok_or(SnapshotError::Malformed)?;
+        let mut game = Self::restored(header);
+
+        for _ in 0..varint_usize(&mut reader)? {
+            let segment = read_location(&mut reader, width, height)?;
+            game.restore_segment(segment)?;
+        }
+
+        let mut index = 0;
+        while index < size {
+            let square = reader
+                .u8()
+                .and_then(encoding::square_from_u8)
+                .ok_or(SnapshotError::Malformed)?;
+            let run = varint_usize(&mut reader)?;
+            let end = index
+                .checked_add(run)
+                .filter(|end| run > 0 && *end <= size)
+                .ok_or(SnapshotError::Malformed)?;
+            game.board.data.as_mut()[index..end].fill(square);
+            index = end;
+        }
+
+        for _ in 0..varint_usize(&mut reader)? {
+            let spawn = read_location(&mut reader, width, height)?;
+            game.add_fruit_spawn(spawn);
+        }
+
+        game.initial_direction = reader
+            .u8()
+            .and_then(encoding::direction_from_u8)
+            .ok_or(SnapshotError::Malformed)?;
+        let length = varint_usize(&mut reader)?;
+        if length == 0 || length > size {
+            return Err(SnapshotError::Malformed);
+        }
+        for i in 0..length {
+            game.initial_snake[i] = read_location(&mut reader, width, height)?;
+        }
+        game.initial_length = length;
+
+        game.fruit_policy = encoding::read_fruit_policy(&mut reader).ok_or(SnapshotError::Malformed)?;
+        game.pending_growth = varint_usize(&mut reader)?;
+        let count = varint_usize(&mut reader)?;
+        if count > MAX_EXTRA_FRUITS {
+            return Err(SnapshotError::Malformed);
+        }
+        for slot in &mut game.extra_fruits[..count] {
+            *slot = Some(read_fruit(&mut reader, width, height).ok_or(SnapshotError::Malformed)?);
+        }
+
+        if !reader.is_empty() {
+            return Err(SnapshotError::Malformed);
+        }
+
+        game.check_restored()?;
+        Ok(game)
+    }
+
+    fn read_header(reader: &mut Reader, width: usize, height: usize) -> Option<Header> {
+        let boundary_mode = encoding::boundary_mode_from_u8(reader.u8()?)?;
+        let direction = encoding::direction_from_u8(reader.u8()?)?;
+        let mut turns = TurnQueue::default();
+        for _ in 0..reader.varint()? {
+            if !turns.push(encoding::direction_from_u8(reader.u8()?)?, direction) {
+                return None;
+            }
+        }
+        let status = encoding::status_from_u8(reader.u8()?)?;
+        let score = u32::try_from(reader.varint()?).ok()?;
+        let ticks = u32::try_from(reader.varint()?).ok()?;
+        let fruit = match reader.varint()?.checked_sub(1) {
+            Some(index) => Some(location_at(usize::try_from(index).ok()?, width, height).ok()?),
+            None => None,
+        };
+        let rng = u128::from(reader.u64_le()?) | (u128::from(reader.u64_le()?) << 64);
+
+        Some(Header {
+            width,
+            height,
+            boundary_mode,
+            direction,
+            turns,
+            status,
+            score,
+            ticks,
+            fruit,
+            rng,
+        })
+    }
+
+    fn restored(header: Header) -> Self {
+        let mut game = Self::empty(
+            header.width,
+            header.height,
+            header.boundary_mode,
+            header.direction,
+            R::from_state(header.rng),
+        );
+        game.turns = header.turns;
+        game.status = header.status;
+        game.score = header.score;
+        game.ticks = header.ticks;
+        game.fruit = header.fruit;
+        game
+    }
+
+    fn restore_segment(&mut self, location: Location) -> Result<(), SnapshotError> {
+        if self.snake_size == self.size() {
+            return Err(SnapshotError::Malformed);
+        }
+        self.snake[self.snake_size] = location;
+        self.snake_size += 1;
+        Ok(())
+    }
+
+    /// Checks that the snake and the fruits are where the board says they are
+    fn check_restored(&self) -> Result<(), SnapshotError> {
+        if self.snake_size == 0 || self.pending_growth > self.size() {
+            return Err(SnapshotError::Malformed);
+        }
+        // Squares of numbered players only come from multiplayer games
+        if self.board.data.as_ref().iter().any(|s| matches!(s, Square::Player(_))) {
+            return Err(SnapshotError::Malformed);
+        }
+
+        let initial_snake = &self.initial_snake.as_ref()[..self.initial_length];
+        if !self.is_whole_snake(&self.snake.as_ref()[..self.snake_size])
+            || !self.is_whole_snake(initial_snake)
+            || initial_snake.iter().any(|l| self.board.at(*l) == Square::Wall)
+        {
+            return Err(SnapshotError::Malformed);
+        }
+
+        let mut extra_fruits = self.extra_fruits.iter().flatten();
+        let extra_fruits_consistent = match self.status {
+            GameStatus::InProgress | GameStatus::Paused => {
+                extra_fruits.all(|f| Some(f.at) != self.fruit && self.board.at(f.at) == Square::Fruit(f.kind))
+            }
+            GameStatus::Lost | GameStatus::Won => extra_fruits.next().is_none(),
+        };
+        if !extra_fruits_consistent {
+            return Err(SnapshotError::Malformed);
+        }
+
+        let snake_squares = self.board.data.as_ref().iter().filter(|s| **s == Square::Snake).count();
+        let consistent = match self.status {
+            GameStatus::Lost => snake_squares == 0,
+            _ => {
+                snake_squares == self.snake_size
+                    && (0..self.snake_size).all(|i| self.board.at(self.snake[i]) == Square::Snake)
+                    && (self.status == GameStatus::Won
+                        || self.fruit.is_none_or(|fruit| self.board.at(fruit) == Square::Fruit(FruitKind::Apple)))
+            }
+        };
+
+        if consistent {
+            Ok(())
+        } else {
+            Err(SnapshotError::Malformed)
+        }
+    }
+
+    /// Whether no two `segments` are on the same square and each one is a move away from the
+    /// one before it, across the edges that wrap
+    fn is_whole_snake(&self, segments: &[Location]) -> bool {
+        let mut taken = S::array(false, self.size());
+        let mut previous: Option<Location> = None;
+        for segment in segments {
+            let follows = |from: Location| {
+                let moved = |d: Direction| self.boundary_mode.apply(from.move_in(d), self.width, self.height);
+                DIRECTIONS.into_iter().any(|d| moved(d) == Some(*segment))
+            };
+            if taken[self.index(*segment)] || !previous.is_none_or(follows) {
+                return false;
+            }
+            taken[self.index(*segment)] = true;
+            previous = Some(*segment);
+        }
+        true
+    }
+}
+
+fn varint_usize(reader: &mut Reader) -> Result<usize, SnapshotError> {
+    reader
+        .varint()
+        .and_then(|v| usize::try_from(v).ok())
+        .ok_or(SnapshotError::Malformed)
+}
+
+fn read_fruit(reader: &mut Reader, width: usize, height: usize) -> Option<Fruit> {
+    let at = read_location(reader, width, height).ok()?;
+    let kind = encoding::fruit_kind_from_u8(reader.u8()?)?;
+    let expires = match reader.varint()?.checked_sub(1) {
+        Some(tick) => Some(u32::try_from(tick).ok()?),
+        None => None,
+    };
+    Some(Fruit { at, kind, expires })
+}
+
+fn read_location(reader: &mut Reader, width: usize, height: usize) -> Result<Location, SnapshotError> {
+    location_at(varint_usize(reader)?, width, height)
+}
+
+fn location_at(index: usize, width: usize, height: usize) -> Result<Location, SnapshotError> {
+    match index {
+        index if index < width * height => Ok(Location::new(index % width, index / width)),
+        _ => Err(SnapshotError::Malformed),
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use self::serde_format::GameState;
+
+#[cfg(feature = "serde")]
+mod serde_format {
+    use serde::{Deserialize, Serialize};
+
+    use super::{Header, SnapshotError};
+    use crate::{
+        Board, BoundaryMode, Direction, Fruit, FruitPolicy, GameStatus, GenericGame, Location, RandomNumberGenerator,
+        Square, Storage, TurnQueue, MAX_EXTRA_FRUITS,
+    };
+
+    /// The complete state of a game in a shape that serde can turn into any format
+    ///
+    /// Carries the same information as the binary snapshot.
+    #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+    pub struct GameState {
+        pub version: u8,
+        pub width: usize,
+        pub height: usize,
+        pub boundary_mode: BoundaryMode,
+        pub direction: Direction,
+        /// Turns waiting to be applied, the first one on the next tick
+        pub turns: Vec<Direction>,
+        pub status: GameStatus,
+        pub score: u32,
+        pub ticks: u32,
+        /// The apple, none while extra fruits cover every free square
+        pub fruit: Option<Location>,
+        /// Generator state, the low half first
+        pub rng: [u64; 2],
+        /// From the tail to the head
+        pub snake: Vec<Location>,
+        /// Row by row
+        pub board: Vec<Square>,
+        pub fruit_spawns: Option<Vec<Location>>,
+        /// The direction and snake `restart` goes back to, from the tail to the head
+        pub initial_direction: Direction,
+        pub initial_snake: Vec<Location>,
+        pub fruit_policy: FruitPolicy,
+        /// Segments the snake is still to grow by
+        pub pending_growth: usize,
+        pub extra_fruits: Vec<Fruit>,
+    }
+
+    impl<S, R> GenericGame<S, R>
+    where
+        S: Storage,
+        R: RandomNumberGenerator,
+    {
+        pub fn to_state(&self) -> GameState {
+            let rng = self.rng.state();
+
+            GameState {
+                version: super::VERSION,
+                width: self.width,
+                height: self.height,
+                boundary_mode: self.boundary_mode,
+                direction: self.current_direction,
+                turns: self.turns.as_slice().to_vec(),
+                status: self.status,
+                score: self.score,
+                ticks: self.ticks,
+                fruit: self.fruit,
+                rng: [rng as u64, (rng >> 64) as u64],
+                snake: (0..self.snake_size)
+                    .map(|i| self.snake[(self.snake_tail + i) % self.size()])
+                    .collect(),
+                board: self.board.iter().map(|(_, square)| square).collect(),
+                fruit_spawns: self.fruit_spawns.as_ref().map(|spawns| {
+                    self.board
+                        .iter()
+                        .map(|(location, _)| location)
+                        .filter(|l| spawns[self.index(*l)])
+                        .collect()
+                }),
+                initial_direction: self.initial_direction,
+                initial_snake: self.initial_snake.as_ref()[..self.initial_length].to_vec(),
+                fruit_policy: self.fruit_policy,
+                pending_growth: self.pending_growth,
+                extra_fruits: self.extra_fruits.iter().flatten().copied().collect(),
+            }
+        }
+
+        pub fn from_state(state: &GameState) -> Result<Self, SnapshotError> {
+            if state.version != super::VERSION {
+                return Err(SnapshotError::UnsupportedVersion(state.version));
+            }
+            let size = match state.width.checked_mul(state.height) {
+                Some(size) if S::fits(size) => size,
+                _ => {
+                    return Err(SnapshotError::SizeMismatch {
+                        width: state.width,
+                        height: state.height,
+                    })
+                }
+            };
+
+            let mut turns = TurnQueue::default();
+            if !state.turns.iter().all(|turn| turns.push(*turn, state.direction)) {
+                return Err(SnapshotError::Malformed);
+            }
+
+            let within = |l: &Location| l.is_within(state.width, state.height);
+            let spawns = state.fruit_spawns.iter().flatten();
+            if state.board.len() != size
+                || !state.fruit.iter().all(within)
+                || !state.snake.iter().all(within)
+                || !spawns.clone().all(within)
+                || !state.initial_snake.iter().all(within)
+                || state.initial_snake.is_empty()
+                || state.initial_snake.len() > size
+                || state.extra_fruits.len() > MAX_EXTRA_FRUITS
+                || !state.extra_fruits.iter().map(|f| &f.at).all(within)
+            {
+                return Err(SnapshotError::Malformed);
+            }
+
+            let mut game = Self::restored(Header {
+                width: state.width,
+                height: state.height,
+                boundary_mode: state.boundary_mode,
+                direction: state.direction,
+                turns,
+                status: state.status,
+                score: state.score,
+                ticks: state.ticks,
+                fruit: state.fruit,
+                rng: u128::from(state.rng[0]) | (u128::from(state.rng[1]) << 64),
+            });
+
+            for segment in &state.snake {
+                game.restore_segment(*segment)?;
+            }
+            game.board.data.as_mut().copy_from_slice(&state.board);
+            for spawn in spawns {
+                game.add_fruit_spawn(*spawn);
+            }
+            game.initial_direction = state.initial_direction;
+            game.initial_snake.as_mut()[..state.initial_snake.len()].copy_from_slice(&state.initial_snake);
+            game.initial_length = state.initial_snake.len();
+            game.fruit_policy = state.fruit_policy;
+            game.pending_growth = state.pending_growth;
+            for (slot, fruit) in game.extra_fruits.iter_mut().zip(&state.extra_fruits) {
+                *slot = Some(*fruit);
+            }
+
+            game.check_restored()?;
+            Ok(game)
+        }
+    }
+}
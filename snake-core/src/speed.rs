@@ -0,0 +1,126 @@
+//! How long a frontend waits between ticks as the game goes on.
+
+use crate::Snake;
+
+/// Shortest interval unless another minimum is set
+const MIN_INTERVAL_MS: u32 = 10;
+
+/// What the level is counted from
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SpeedMetric {
+    Score,
+    Length,
+}
+
+/// How the tick interval shrinks from one level to the next
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SpeedCurve {
+    /// The interval never changes
+    Constant,
+    /// Every level is `step_ms` shorter than the previous one
+    Linear { step_ms: u32 },
+    /// Every level takes `percent` of the previous one's interval
+    Exponential { percent: u32 },
+}
+
+/// Reports the tick interval for the level a game is on
+///
+/// Levels start at 1 and go up every `every` points of the metric, the interval never drops
+/// below the minimum.
+///
+/// ```rust
+/// use self::snake::{Speed, SpeedCurve, SpeedMetric};
+/// let speed = Speed::new(200)
+///     .curve(SpeedCurve::Linear { step_ms: 20 })
+///     .per(SpeedMetric::Score, 5)
+///     .min_interval_ms(100);
+///
+/// assert_eq!(200, speed.interval_at(1));
+/// assert_eq!(160, speed.interval_at(3));
+/// assert_eq!(100, speed.interval_at(10));
+/// ```
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Speed {
+    start_ms: u32,
+    min_ms: u32,
+    curve: SpeedCurve,
+    metric: SpeedMetric,
+    every: u32,
+}
+
+impl Default for Speed {
+    /// 200ms at first, 10% faster every 5 fruits down to 60ms
+    fn default() -> Self {
+        Speed::new(200)
+            .curve(SpeedCurve::Exponential { percent: 90 })
+            .per(SpeedMetric::Score, 5)
+            .min_interval_ms(60)
+    }
+}
+
+impl Speed {
+    /// Constant `start_ms` interval, a level per fruit eaten, never shorter than 10ms
+    pub const fn new(start_ms: u32) -> Self {
+        Speed {
+            start_ms,
+            min_ms: MIN_INTERVAL_MS,
+            curve: SpeedCurve::Constant,
+            metric: SpeedMetric::Score,
+            every: 1,
+        }
+    }
+
+    pub const fn curve(mut self, curve: SpeedCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Goes up a level every `every` points of `metric`, for the length that is every `every`
+    /// segments including the ones the snake started with
+    pub const fn per(mut self, metric: SpeedMetric, every: u32) -> Self {
+        self.metric = metric;
+        self.every = if every == 0 { 1 } else { every };
+        self
+    }
+
+    /// Never waits less than `min_ms` between ticks, at least 1ms
+    pub const fn min_interval_ms(mut self, min_ms: u32) -> Self {
+        self.min_ms = if min_ms == 0 { 1 } else { min_ms };
+        self
+    }
+
+    /// Level the game is on, starting from 1
+    pub fn level(&self, game: &dyn Snake) -> u32 {
+        let points = match self.metric {
+            SpeedMetric::Score => game.score(),
+            SpeedMetric::Length => game.length() as u32,
+        };
+        points / self.every + 1
+    }
+
+    /// How long to wait before the next tick of `game`
+    pub fn interval_ms(&self, game: &dyn Snake) -> u32 {
+        self.interval_at(self.level(game))
+    }
+
+    pub fn interval_at(&self, level: u32) -> u32 {
+        let steps = level.saturating_sub(1);
+        let interval = match self.curve {
+            SpeedCurve::Constant => self.start_ms,
+            SpeedCurve::Linear { step_ms } => self.start_ms.saturating_sub(steps.saturating_mul(step_ms)),
+            SpeedCurve::Exponential { percent } if percent >= 100 => self.start_ms,
+            SpeedCurve::Exponential { percent } => {
+                let mut interval = self.start_ms;
+                for _ in 0..steps {
+                    if interval <= self.min_ms {
+                        break;
+                    }
+                    // Less than `interval`, so it fits back into a u32
+                    interval = (u64::from(interval) * u64::from(percent) / 100) as u32;
+                }
+                interval
+            }
+        };
+        interval.max(self.min_ms)
+    }
+}
@@ -0,0 +1,47 @@
+//! Where boards and games keep their squares and snake segments.
+
+use core::ops::{Index, IndexMut};
+
+/// Decides how many squares a game can have and where they are kept
+pub trait Storage {
+    type Array<T: Copy>: AsRef<[T]> + AsMut<[T]> + Index<usize, Output = T> + IndexMut<usize>;
+
+    /// Whether a board of `size` squares can be stored
+    fn fits(size: usize) -> bool;
+    /// Creates `size` copies of `value`, panics when `size` doesn't fit
+    fn array<T: Copy>(value: T, size: usize) -> Self::Array<T>;
+}
+
+/// Arrays of exactly `SIZE` elements, the size of the board is known at compile time and
+/// nothing is allocated
+pub struct Fixed<const SIZE: usize>;
+
+impl<const SIZE: usize> Storage for Fixed<SIZE> {
+    type Array<T: Copy> = [T; SIZE];
+
+    fn fits(size: usize) -> bool {
+        size == SIZE
+    }
+
+    fn array<T: Copy>(value: T, size: usize) -> [T; SIZE] {
+        assert_eq!(SIZE, size);
+        [value; SIZE]
+    }
+}
+
+/// Vectors allocated on the heap, any board size can be picked at runtime
+#[cfg(feature = "std")]
+pub struct Dynamic;
+
+#[cfg(feature = "std")]
+impl Storage for Dynamic {
+    type Array<T: Copy> = Vec<T>;
+
+    fn fits(_: usize) -> bool {
+        true
+    }
+
+    fn array<T: Copy>(value: T, size: usize) -> Vec<T> {
+        vec![value; size]
+    }
+}
@@ -0,0 +1,334 @@
+//! Streaming a game tick by tick, to watch it somewhere else or to keep it.
+//!
+//! Unlike a replay a stream doesn't need the engine to be played back, it has what the board
+//! looked like after every tick. The binary format, integers are LEB128 varints unless stated
+//! otherwise and locations are stored as `y * width + x`:
+//!
+//! * `SNKT` magic and a version byte
+//! * width, height
+//! * frames, each one is its length in bytes followed by
+//!   * kind byte, 0 for a keyframe and 1 for a delta
+//!   * milliseconds since the stream started, tick, score, status byte
+//!   * for keyframes the board as runs of a square byte followed by the run length
+//!   * for deltas the number of squares that changed since the previous frame and for each
+//!     its location and square byte
+//!
+//! The first frame is always a keyframe. Later ones come every so often so that a stream cut
+//! at a frame boundary can be picked up from its next keyframe.
+
+use std::io::{self, Read, Write};
+use std::time::Instant;
+
+use crate::encoding::{self, BufferFull, Reader, Writer};
+use crate::{Board, Dynamic, GameStatus, GridBoard, Location, Snake, Square};
+
+const MAGIC: &[u8; 4] = b"SNKT";
+const VERSION: u8 = 1;
+
+const KEYFRAME: u8 = 0;
+const DELTA: u8 = 1;
+
+/// Longest varint of a `u64`
+const MAX_VARINT: usize = 10;
+
+/// Boards with more squares than that are taken for a corrupted header
+const MAX_SQUARES: usize = 1 << 24;
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "game stream is malformed")
+}
+
+/// Longest frame on a board of `squares` squares, a keyframe of runs of one square each or a
+/// delta changing every square
+fn max_frame_len(squares: usize) -> usize {
+    1 + 3 * MAX_VARINT + 1 + MAX_VARINT + squares * (1 + MAX_VARINT)
+}
+
+/// Writes frames of a game to `W` as they're played
+///
+/// ```rust
+/// use self::snake::{Board, Game, Pcg32, Snake, StreamReader, StreamWriter};
+/// let mut game = Game::<{ 8 * 8 }, Pcg32>::new(8, 8);
+/// let mut stream = StreamWriter::new(Vec::new(), 8, 8).unwrap();
+/// stream.write(&game).unwrap();
+/// game.advance();
+/// stream.write(&game).unwrap();
+///
+/// let bytes = stream.into_inner();
+/// let mut reader = StreamReader::new(&bytes[..]).unwrap();
+/// assert_eq!(0, reader.next_frame().unwrap().unwrap().tick);
+/// assert_eq!(1, reader.next_frame().unwrap().unwrap().tick);
+/// assert!(reader.next_frame().unwrap().is_none());
+/// assert!(reader.board().iter().eq(game.board().iter()));
+/// ```
+pub struct StreamWriter<W: Write> {
+    out: W,
+    /// The board as of the last frame written
+    shown: GridBoard<Dynamic>,
+    started: Instant,
+    frames: u32,
+    keyframe_every: u32,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Writes the header of a stream of a board of `width` by `height` squares
+    pub fn new(mut out: W, width: usize, height: usize) -> io::Result<Self> {
+        let mut header = [0; MAGIC.len() + 1 + 2 * MAX_VARINT];
+        let mut writer = Writer::new(&mut header);
+        writer.bytes(MAGIC).map_err(|_| malformed())?;
+        writer.u8(VERSION).map_err(|_| malformed())?;
+        writer.varint(width as u64).map_err(|_| malformed())?;
+        writer.varint(height as u64).map_err(|_| malformed())?;
+        let length = writer.position();
+        out.write_all(&header[..length])?;
+
+        Ok(StreamWriter {
+            out,
+            shown: GridBoard::new(width, height),
+            started: Instant::now(),
+            frames: 0,
+            keyframe_every: 100,
+            buffer: vec![0; max_frame_len(width * height)],
+        })
+    }
+
+    /// Writes every `frames` frame as a keyframe, 100 unless set, 0 for only the first one
+    pub fn keyframe_every(mut self, frames: u32) -> Self {
+        self.keyframe_every = frames;
+        self
+    }
+
+    /// Writes a frame of `game` as it is now and flushes it, the game must be on a board of
+    /// the size the stream was made for
+    pub fn write(&mut self, game: &dyn Snake) -> io::Result<()> {
+        let board = game.board();
+        if (board.width(), board.height()) != (self.shown.width(), self.shown.height()) {
+            let message = format!("the stream is of a {}x{} board", self.shown.width(), self.shown.height());
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+
+        let keyframe = self.frames.is_multiple_of(self.keyframe_every);
+        let millis = self.started.elapsed().as_millis() as u64;
+        let length = self
+            .encode(board, keyframe, millis, game.ticks(), game.score(), game.status())
+            .map_err(|_| malformed())?;
+
+        let mut prefix = [0; MAX_VARINT];
+        let mut writer = Writer::new(&mut prefix);
+        writer.varint(length as u64).map_err(|_| malformed())?;
+        let prefix_length = writer.position();
+        self.out.write_all(&prefix[..prefix_length])?;
+        self.out.write_all(&self.buffer[..length])?;
+        self.out.flush()?;
+
+        for (location, square) in board.iter() {
+            *self.shown.at_mut(&location) = square;
+        }
+        self.frames = self.frames.wrapping_add(1);
+        Ok(())
+    }
+
+    fn encode(
+        &mut self,
+        board: &dyn Board,
+        keyframe: bool,
+        millis: u64,
+        tick: u32,
+        score: u32,
+        status: GameStatus,
+    ) -> Result<usize, BufferFull> {
+        let width = self.shown.width();
+        let mut writer = Writer::new(&mut self.buffer);
+        writer.u8(if keyframe { KEYFRAME } else { DELTA })?;
+        writer.varint(millis)?;
+        writer.varint(u64::from(tick))?;
+        writer.varint(u64::from(score))?;
+        writer.u8(encoding::status_to_u8(status))?;
+
+        if keyframe {
+            let mut squares = board.iter().map(|(_, square)| square).peekable();
+            while let Some(square) = squares.next() {
+                let mut run = 1;
+                while squares.next_if_eq(&square).is_some() {
+                    run += 1;
+                }
+                writer.u8(encoding::square_to_u8(square))?;
+                writer.varint(run)?;
+            }
+        } else {
+            let shown = &self.shown;
+            let changed = || board.iter().filter(|(location, square)| shown.at(*location) != *square);
+            writer.varint(changed().count() as u64)?;
+            for (location, square) in changed() {
+                writer.varint(location.y as u64 * width as u64 + location.x as u64)?;
+                writer.u8(encoding::square_to_u8(square))?;
+            }
+        }
+        Ok(writer.position())
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.out
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// A frame read back from a stream
+#[derive(PartialEq, Clone, Debug)]
+pub struct Frame {
+    /// Milliseconds between the start of the stream and the frame
+    pub millis: u64,
+    pub tick: u32,
+    pub score: u32,
+    pub status: GameStatus,
+    pub keyframe: bool,
+    /// Squares that differ from the board of the previous frame, keyframes included
+    pub changes: Vec<(Location, Square)>,
+}
+
+/// Reads frames written by a `StreamWriter` and keeps the board they add up to
+pub struct StreamReader<R: Read> {
+    input: R,
+    board: GridBoard<Dynamic>,
+    /// A delta only makes sense on top of a keyframe
+    seen_keyframe: bool,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> StreamReader<R> {
+    /// Reads the header, fails with `InvalidData` when it's not one of a stream
+    pub fn new(mut input: R) -> io::Result<Self> {
+        let mut magic = [0; MAGIC.len() + 1];
+        input.read_exact(&mut magic)?;
+        if magic[..MAGIC.len()] != MAGIC[..] {
+            return Err(malformed());
+        }
+        if magic[MAGIC.len()] != VERSION {
+            let message = format!("game stream version {} is not supported", magic[MAGIC.len()]);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+
+        let width = usize::try_from(read_varint(&mut input)?.ok_or_else(malformed)?).map_err(|_| malformed())?;
+        let height = usize::try_from(read_varint(&mut input)?.ok_or_else(malformed)?).map_err(|_| malformed())?;
+        match width.checked_mul(height) {
+            Some(squares) if squares > 0 && squares <= MAX_SQUARES => {}
+            _ => return Err(malformed()),
+        }
+
+        Ok(StreamReader {
+            input,
+            board: GridBoard::new(width, height),
+            seen_keyframe: false,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// The board as of the last frame read, empty before the first one
+    pub fn board(&self) -> &dyn Board {
+        &self.board
+    }
+
+    /// Reads the next frame, `None` once the stream ended after a whole frame
+    pub fn next_frame(&mut self) -> io::Result<Option<Frame>> {
+        let Some(length) = read_varint(&mut self.input)? else {
+            return Ok(None);
+        };
+        let length = usize::try_from(length)
+            .ok()
+            .filter(|length| *length <= max_frame_len(self.board.width() * self.board.height()))
+            .ok_or_else(malformed)?;
+        self.buffer.resize(length, 0);
+        self.input.read_exact(&mut self.buffer)?;
+
+        let frame = decode(&self.buffer, &self.board, self.seen_keyframe).ok_or_else(malformed)?;
+        for (location, square) in &frame.changes {
+            *self.board.at_mut(location) = *square;
+        }
+        self.seen_keyframe |= frame.keyframe;
+        Ok(Some(frame))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+}
+
+impl<R: Read> Iterator for StreamReader<R> {
+    type Item = io::Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+
+/// `None` when the input ends before the first byte, an error when it ends after it
+fn read_varint(input: &mut impl Read) -> io::Result<Option<u64>> {
+    let mut result = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        if let Err(error) = input.read_exact(&mut byte) {
+            return match error.kind() {
+                io::ErrorKind::UnexpectedEof if shift == 0 => Ok(None),
+                _ => Err(error),
+            };
+        }
+        result |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(result));
+        }
+    }
+    Err(malformed())
+}
+
+fn decode(bytes: &[u8], board: &GridBoard<Dynamic>, seen_keyframe: bool) -> Option<Frame> {
+    let (width, height) = (board.width(), board.height());
+    let mut reader = Reader::new(bytes);
+    let keyframe = match reader.u8()? {
+        KEYFRAME => true,
+        DELTA if seen_keyframe => false,
+        _ => return None,
+    };
+    let millis = reader.varint()?;
+    let tick = u32::try_from(reader.varint()?).ok()?;
+    let score = u32::try_from(reader.varint()?).ok()?;
+    let status = encoding::status_from_u8(reader.u8()?)?;
+
+    let mut changes = Vec::new();
+    if keyframe {
+        let mut index = 0;
+        while index < width * height {
+            let square = encoding::square_from_u8(reader.u8()?)?;
+            let run = usize::try_from(reader.varint()?).ok().filter(|run| *run > 0)?;
+            let end = index.checked_add(run).filter(|end| *end <= width * height)?;
+            for i in index..end {
+                let location = Location::new(i % width, i / width);
+                if board.at(location) != square {
+                    changes.push((location, square));
+                }
+            }
+            index = end;
+        }
+    } else {
+        for _ in 0..reader.varint()? {
+            let i = usize::try_from(reader.varint()?).ok().filter(|i| *i < width * height)?;
+            let square = encoding::square_from_u8(reader.u8()?)?;
+            changes.push((Location::new(i % width, i / width), square));
+        }
+    }
+
+    if !reader.is_empty() {
+        return None;
+    }
+    Some(Frame {
+        millis,
+        tick,
+        score,
+        status,
+        keyframe,
+        changes,
+    })
+}
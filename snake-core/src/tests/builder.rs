@@ -0,0 +1,134 @@
+use crate::*;
+
+use super::test_utils::*;
+
+type TestGame = Game<{ 6 * 4 }, HardcodedNumbersGenerator>;
+
+#[test]
+fn builder_defaults_match_new() {
+    let built = GameBuilder::new(6, 4).build::<Fixed<{ 6 * 4 }>, HardcodedNumbersGenerator>();
+    let new = TestGame::new(6, 4);
+
+    assert!(built.board().iter().eq(new.board().iter()));
+    assert_eq!(new.direction(), built.direction());
+}
+
+#[test]
+fn snake_trails_behind_the_head() {
+    let game: TestGame = GameBuilder::new(6, 4)
+        .initial_length(3)
+        .direction(Direction::Down)
+        .start(Location::new(1, 2))
+        .build();
+
+    assert_eq!(Location::new(1, 2), game.head());
+    assert_eq!(Direction::Down, game.direction());
+    assert_board!(game.board(), &board_layout!(
+        " O    ",
+        " O    ",
+        " O  F ",
+        "      "
+    ));
+}
+
+#[test]
+fn level_takes_over_the_size_and_the_snake() {
+    let level = Level::parse("#####\n#OO>#\n#F###").unwrap();
+
+    let game: Game<{ 5 * 3 }, HardcodedNumbersGenerator> = GameBuilder::new(1, 1)
+        .initial_length(5)
+        .level(&level)
+        .build();
+
+    assert_eq!(3, game.length());
+    assert_eq!(Location::new(3, 1), game.head());
+    assert_eq!(Some(Location::new(1, 2)), game.fruit());
+}
+
+#[test]
+fn same_seed_gives_the_same_game() {
+    let builder = GameBuilder::new(9, 9).seed(11);
+
+    let mut first = builder.build::<Fixed<{ 9 * 9 }>, Pcg32>();
+    let mut second = builder.build::<Fixed<{ 9 * 9 }>, Pcg32>();
+
+    for _ in 0..20 {
+        first.advance();
+        second.advance();
+        assert_eq!(first.fruit(), second.fruit());
+    }
+}
+
+#[test]
+fn macro_computes_the_size() {
+    let game = create_game_instance!(7, 5, HardcodedNumbersGenerator, |b| b.boundary_mode(BoundaryMode::Solid));
+
+    assert_eq!((7, 5), (game.board().width(), game.board().height()));
+    assert_eq!(Location::new(3, 2), game.head());
+}
+
+#[test]
+#[should_panic(expected = "snake does not fit on the board")]
+fn snake_sticking_out_of_the_board_panics() {
+    let _: TestGame = GameBuilder::new(6, 4)
+        .initial_length(4)
+        .start(Location::new(2, 0))
+        .build();
+}
+
+#[test]
+fn snake_can_be_placed_on_a_level() {
+    let level = Level::parse("#####\n#OO>#\n#F###").unwrap();
+
+    let game: Game<{ 5 * 3 }, HardcodedNumbersGenerator> = GameBuilder::new(5, 3)
+        .level(&level)
+        .direction(Direction::Left)
+        .initial_length(1)
+        .start(Location::new(1, 1))
+        .build();
+
+    assert_eq!(Direction::Left, game.direction());
+    assert_board!(game.board(), &board_layout!(
+        "#####",
+        "#O  #",
+        "#F###"
+    ));
+}
+
+#[test]
+fn snake_is_checked_against_the_board() {
+    let walls = [Location::new(4, 1)];
+    let builder = GameBuilder::new(6, 4).walls(&walls);
+
+    let result = |builder: GameBuilder| builder.try_build::<Fixed<{ 6 * 4 }>, HardcodedNumbersGenerator>().err();
+
+    assert_eq!(None, result(builder.clone()));
+    assert_eq!(Some(BuildError::NoSnake), result(builder.clone().initial_length(0)));
+    assert_eq!(
+        Some(BuildError::OffBoard { at: Location::new(-1, 0) }),
+        result(builder.clone().initial_length(3).start(Location::new(1, 0)))
+    );
+    assert_eq!(
+        Some(BuildError::Obstacle { at: Location::new(4, 1) }),
+        result(builder.clone().direction(Direction::Up).initial_length(3).start(Location::new(4, 0)))
+    );
+    assert_eq!(
+        Some(BuildError::DoesNotFit { width: 6, height: 5 }),
+        GameBuilder::new(6, 5).try_build::<Fixed<{ 6 * 4 }>, HardcodedNumbersGenerator>().err()
+    );
+}
+
+#[test]
+fn level_snake_is_checked_against_extra_walls() {
+    let level = Level::parse("#####\n#OO>#\n#F###").unwrap();
+    let walls = [Location::new(2, 1)];
+
+    assert_eq!(
+        Some(BuildError::Obstacle { at: Location::new(2, 1) }),
+        GameBuilder::new(5, 3)
+            .level(&level)
+            .walls(&walls)
+            .try_build::<Fixed<{ 5 * 3 }>, HardcodedNumbersGenerator>()
+            .err()
+    );
+}
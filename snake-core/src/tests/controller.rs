@@ -0,0 +1,81 @@
+use crate::*;
+
+use super::test_utils::*;
+
+/// Lets `controller` play until the game ends or `ticks` run out
+fn autoplay(game: &mut impl Snake, controller: &mut impl Controller, ticks: u32) -> GameStatus {
+    for _ in 0..ticks {
+        controller.steer(game);
+        if game.advance() != GameStatus::InProgress {
+            break;
+        }
+    }
+    game.status()
+}
+
+#[test]
+fn greedy_keeps_eating_on_an_open_board() {
+    for seed in 0..5 {
+        let mut game: VecGame<Pcg32> = GameBuilder::new(10, 10).seed(seed).build();
+        let mut greedy = Greedy::<Dynamic>::new(10, 10);
+
+        autoplay(&mut game, &mut greedy, 2000);
+
+        assert!(game.score() >= 30, "seed {} scored {}", seed, game.score());
+    }
+}
+
+#[test]
+fn greedy_finds_its_way_around_walls() {
+    let level = Level::parse(include_str!("../../../levels/pillars.txt")).unwrap();
+    let mut game: VecGame<Pcg32> = GameBuilder::new(1, 1)
+        .level(&level)
+        .boundary_mode(BoundaryMode::Solid)
+        .build();
+    let mut greedy = Greedy::<Dynamic>::new(game.board().width(), game.board().height());
+
+    autoplay(&mut game, &mut greedy, 3000);
+
+    assert!(game.score() >= 30, "scored {}", game.score());
+}
+
+#[test]
+fn greedy_never_turns_back_into_its_neck() {
+    let mut game = Game::<{ 5 * 5 }, HardcodedNumbersGenerator>::new(5, 5);
+    let mut greedy = Greedy::<Fixed<{ 5 * 5 }>>::new(5, 5);
+
+    for _ in 0..50 {
+        if let Some(direction) = greedy.next_direction(&game) {
+            assert_ne!(game.direction().opposite(), direction);
+            game.set_direction(direction);
+        }
+        game.advance();
+    }
+}
+
+#[test]
+fn hamiltonian_wins_on_boards_with_an_even_side() {
+    for (width, height, boundary_mode) in [(6, 6, BoundaryMode::Wrap), (4, 5, BoundaryMode::Solid), (7, 2, BoundaryMode::Solid)] {
+        let mut game: VecGame<Pcg32> = GameBuilder::new(width, height).boundary_mode(boundary_mode).build();
+        let mut hamiltonian = Hamiltonian::<Dynamic>::new(width, height).unwrap();
+
+        assert_eq!(GameStatus::Won, autoplay(&mut game, &mut hamiltonian, 10_000), "{}x{}", width, height);
+        assert_eq!(width * height, game.length());
+    }
+}
+
+#[test]
+fn hamiltonian_needs_an_even_side() {
+    assert!(Hamiltonian::<Dynamic>::new(5, 5).is_none());
+    assert!(Hamiltonian::<Dynamic>::new(1, 4).is_none());
+    assert!(Hamiltonian::<Dynamic>::new(5, 4).is_some());
+}
+
+#[test]
+fn controllers_leave_finished_games_alone() {
+    let mut game = Game::<{ 5 * 5 }, HardcodedNumbersGenerator>::new(5, 5);
+    game.pause();
+
+    assert_eq!(None, Greedy::<Fixed<{ 5 * 5 }>>::new(5, 5).next_direction(&game));
+    assert_eq!(None, Hamiltonian::<Fixed<{ 5 * 4 }>>::new(5, 4).unwrap().next_direction(&game));
+}
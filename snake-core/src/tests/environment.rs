@@ -0,0 +1,113 @@
+use crate::*;
+
+/// 5x5 board with the snake in the middle heading right and the apple right ahead
+fn environment(observation: Observation, rewards: Rewards) -> Environment {
+    Environment::new(GameBuilder::new(5, 5).seed(0), observation, rewards).unwrap()
+}
+
+fn play(env: &mut Environment, actions: &[Action]) -> Vec<(Vec<f32>, f32, bool)> {
+    actions
+        .iter()
+        .map(|action| {
+            let (observation, reward, done) = env.step(*action);
+            (observation.to_vec(), reward, done)
+        })
+        .collect()
+}
+
+#[test]
+fn same_seed_plays_the_same_episode() {
+    let actions = [Action::Straight, Action::Left, Action::Straight, Action::Right, Action::Right];
+    let mut env = Environment::new(GameBuilder::new(8, 8), Observation::Grid, Rewards::default()).unwrap();
+
+    env.reset(7);
+    let first = play(&mut env, &actions);
+    env.reset(3);
+    play(&mut env, &actions);
+    env.reset(7);
+
+    assert_eq!(first, play(&mut env, &actions));
+}
+
+#[test]
+fn grid_shows_every_square_in_its_channel() {
+    let env = environment(Observation::Grid, Rewards::default());
+    let observation = env.observation();
+
+    assert_eq!(CHANNELS * 25, observation.len());
+    let ones: Vec<usize> = (0..observation.len()).filter(|i| observation[*i] == 1.0).collect();
+    // Body at (1,2), head at (2,2) and the apple at (3,2)
+    assert_eq!(vec![11, 25 + 12, 50 + 13], ones);
+}
+
+#[test]
+fn window_is_turned_to_where_the_snake_heads() {
+    let mut env = environment(Observation::Window { radius: 1 }, Rewards::default());
+    let fruit = |observation: &[f32]| (0..9).find(|i| observation[2 * 9 + i] == 1.0);
+    assert_eq!(CHANNELS * 9, env.observation_len());
+    assert_eq!(Some(1), fruit(env.observation()));
+
+    let (observation, _, _) = env.step(Action::Left);
+    // Heading up the apple is behind to the right
+    assert_eq!(Some(8), fruit(observation));
+    // The head is in the middle and the body right behind it
+    assert_eq!((1.0, 1.0), (observation[9 + 4], observation[7]));
+}
+
+#[test]
+fn window_shows_solid_edges_as_walls() {
+    let builder = GameBuilder::new(3, 3).boundary_mode(BoundaryMode::Solid);
+    let mut env = Environment::new(builder, Observation::Window { radius: 1 }, Rewards::default()).unwrap();
+
+    let (observation, _, _) = env.step(Action::Straight);
+
+    // Heading right along the middle row into the edge, the whole row ahead is outside
+    let walls = &observation[3 * 9..];
+    assert_eq!([1.0, 1.0, 1.0], walls[0..3]);
+}
+
+#[test]
+fn features_show_danger_and_where_the_apple_is() {
+    let mut env = environment(Observation::Features, Rewards::default());
+    // Heading right with the apple straight ahead and nothing in the way
+    assert_eq!(
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
+        env.observation()[..]
+    );
+
+    let (observation, _, _) = env.step(Action::Left);
+    assert_eq!([0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0], observation[..7]);
+    assert_eq!([0.0, 1.0, 0.0, 1.0], observation[7..]);
+}
+
+#[test]
+fn rewards_add_up() {
+    let rewards = Rewards {
+        point: 10.0,
+        death: -100.0,
+        win: 1000.0,
+        step: -1.0,
+        closer: 0.5,
+    };
+
+    let mut env = environment(Observation::Features, rewards);
+    assert_eq!(-1.5, env.step(Action::Left).1);
+
+    let mut env = environment(Observation::Features, rewards);
+    assert_eq!(9.0, env.step(Action::Straight).1);
+}
+
+#[test]
+fn episode_ends_with_the_game_or_when_the_snake_starves() {
+    let mut env = environment(Observation::Features, Rewards::default()).max_idle(3);
+    let steps = play(&mut env, &[Action::Left, Action::Straight, Action::Straight, Action::Straight]);
+
+    assert_eq!(vec![false, false, true, true], steps.iter().map(|s| s.2).collect::<Vec<_>>());
+    assert_eq!(0.0, steps[3].1);
+
+    let builder = GameBuilder::new(3, 3).boundary_mode(BoundaryMode::Solid);
+    let mut env = Environment::new(builder, Observation::Features, Rewards::default()).unwrap();
+    let steps = play(&mut env, &[Action::Straight, Action::Straight]);
+    assert_eq!((-1.0, true), (steps[1].1, steps[1].2));
+    assert_eq!(GameStatus::Lost, env.game().status());
+}
@@ -0,0 +1,214 @@
+use crate::*;
+
+use super::test_utils::*;
+
+type TestGame = Game<{ 6 * 4 }, HardcodedNumbersGenerator>;
+
+fn only(kind: FruitKind) -> FruitPolicy {
+    FruitKind::ALL
+        .into_iter()
+        .fold(FruitPolicy::new().extra(1).chance(100), |policy, k| {
+            policy.weight(k, if k == kind { 1 } else { 0 })
+        })
+}
+
+/// Puts an extra fruit right in front of the snake
+fn fruit_ahead(game: &mut TestGame, kind: FruitKind) -> Location {
+    let at = game.head().move_in(game.direction()).wrap(6, 4);
+    game.extra_fruits[0] = Some(Fruit { at, kind, expires: None });
+    *game.board.at_mut(&at) = Square::Fruit(kind);
+    at
+}
+
+/// The snake starts in the top row away from the apple
+fn builder() -> GameBuilder<'static> {
+    GameBuilder::new(6, 4).start(Location::new(2, 0))
+}
+
+fn advance_with_events(game: &mut TestGame) -> Vec<GameEvent> {
+    let mut events = Vec::new();
+    game.advance_with_events(&mut |event| events.push(event));
+    events
+}
+
+#[test]
+fn policy_picks_kinds_by_weight() {
+    let policy = FruitPolicy::new()
+        .weight(FruitKind::Bonus, 1)
+        .weight(FruitKind::Feast, 0)
+        .weight(FruitKind::Shrink, 2);
+
+    assert_eq!(
+        [Some(FruitKind::Bonus), Some(FruitKind::Shrink), Some(FruitKind::Shrink), Some(FruitKind::Bonus)],
+        [0, 1, 2, 3].map(|roll| policy.pick(roll))
+    );
+    assert_eq!(None, only(FruitKind::Apple).weight(FruitKind::Apple, 0).pick(7));
+}
+
+#[test]
+fn extra_fruit_appears_next_to_the_apple() {
+    let mut game: TestGame = builder().fruit_policy(only(FruitKind::Bonus)).build();
+
+    let events = advance_with_events(&mut game);
+
+    let fruits: Vec<Fruit> = game.fruits().collect();
+    assert_eq!(2, fruits.len());
+    assert_eq!(FruitKind::Apple, fruits[0].kind);
+    assert_eq!(FruitKind::Bonus, fruits[1].kind);
+    assert_eq!(Square::Fruit(FruitKind::Bonus), game.board().at(fruits[1].at));
+    assert_eq!(vec![GameEvent::FruitSpawned { at: fruits[1].at }], events);
+
+    game.advance();
+    assert_eq!(2, game.fruits().count());
+}
+
+#[test]
+fn no_extra_fruit_appears_by_default() {
+    let mut game = TestGame::new(6, 4);
+
+    for _ in 0..20 {
+        game.advance();
+        assert_eq!(1, game.fruits().count());
+    }
+}
+
+#[test]
+fn bonus_fruit_scores_more() {
+    let mut game: TestGame = builder().build();
+    let at = fruit_ahead(&mut game, FruitKind::Bonus);
+
+    let events = advance_with_events(&mut game);
+
+    assert_eq!(5, game.score());
+    assert_eq!(3, game.length());
+    assert_eq!(
+        vec![
+            GameEvent::FruitEaten { at, kind: FruitKind::Bonus },
+            GameEvent::Grew { length: 3 }
+        ],
+        events
+    );
+}
+
+#[test]
+fn feast_grows_the_snake_over_the_next_ticks() {
+    let mut game: TestGame = builder().build();
+    fruit_ahead(&mut game, FruitKind::Feast);
+
+    let lengths: Vec<usize> = (0..4)
+        .map(|_| {
+            game.advance();
+            game.length()
+        })
+        .collect();
+
+    assert_eq!(vec![3, 4, 5, 5], lengths);
+    assert_eq!(1, game.score());
+}
+
+#[test]
+fn shrinking_fruit_never_takes_the_head() {
+    let mut game: TestGame = GameBuilder::new(6, 4).initial_length(4).start(Location::new(4, 1)).build();
+    fruit_ahead(&mut game, FruitKind::Shrink);
+
+    let events = advance_with_events(&mut game);
+    assert_eq!(2, game.length());
+    assert!(events.contains(&GameEvent::Shrank { length: 2 }));
+
+    fruit_ahead(&mut game, FruitKind::Shrink);
+    game.advance();
+    assert_eq!(1, game.length());
+    assert_eq!(0, game.score());
+    assert_board!(game.board(), &board_layout!(
+        "      ",
+        "O     ",
+        "    F ",
+        "      "
+    ));
+}
+
+#[test]
+fn extra_fruit_disappears_when_it_expires() {
+    let policy = only(FruitKind::Feast).lifetime(2);
+    let mut game: TestGame = builder().fruit_policy(policy).build();
+
+    game.advance();
+    let fruit = game.fruits().nth(1).unwrap();
+    assert_eq!(Some(3), fruit.expires);
+
+    game.set_direction(Direction::Down);
+    game.advance();
+    let events = advance_with_events(&mut game);
+
+    assert!(events.contains(&GameEvent::FruitExpired { at: fruit.at }));
+    assert_ne!(Square::Fruit(FruitKind::Feast), game.board().at(fruit.at));
+}
+
+#[test]
+fn lost_game_clears_every_fruit() {
+    let mut game: TestGame = GameBuilder::new(6, 4)
+        .boundary_mode(BoundaryMode::Solid)
+        .fruit_policy(only(FruitKind::Shrink))
+        .build();
+    game.advance();
+    assert_eq!(2, game.fruits().count());
+
+    while game.advance() == GameStatus::InProgress {}
+
+    assert_eq!(0, game.fruits().count());
+    assert!(game.board().iter().all(|(_, square)| square == Square::Empty));
+}
+
+#[test]
+fn restart_removes_extra_fruits() {
+    let mut game: TestGame = builder().fruit_policy(only(FruitKind::Bonus)).build();
+    game.advance();
+
+    game.restart();
+
+    assert_eq!(1, game.fruits().count());
+}
+
+/// A single row with the snake on the left, the apple and then a fruit of `kind` ahead of it
+fn row_with_fruit_behind_the_apple(kind: FruitKind) -> Game<4, HardcodedNumbersGenerator> {
+    let mut game: Game<4, HardcodedNumbersGenerator> = GameBuilder::new(4, 1).start(Location::new(1, 0)).build();
+    for square in game.board.data.as_mut() {
+        if matches!(square, Square::Fruit(_)) {
+            *square = Square::Empty;
+        }
+    }
+    game.fruit = Some(Location::new(2, 0));
+    *game.board.at_mut(&Location::new(2, 0)) = Square::Fruit(FruitKind::Apple);
+    game.extra_fruits[0] = Some(Fruit { at: Location::new(3, 0), kind, expires: None });
+    *game.board.at_mut(&Location::new(3, 0)) = Square::Fruit(kind);
+    game
+}
+
+#[test]
+fn game_goes_on_while_an_extra_fruit_takes_the_last_free_square() {
+    let mut game = row_with_fruit_behind_the_apple(FruitKind::Bonus);
+
+    assert_eq!(GameStatus::InProgress, game.advance());
+    assert_eq!(None, game.fruit());
+    assert_eq!(1, game.fruits().count());
+    let restored = Game::<4, HardcodedNumbersGenerator>::restore(&game.to_snapshot()).unwrap();
+    assert_eq!(None, restored.fruit());
+
+    let mut events = Vec::new();
+    assert_eq!(GameStatus::Won, game.advance_with_events(&mut |event| events.push(event)));
+    assert_eq!(Some(&GameEvent::Won), events.last());
+}
+
+#[test]
+fn apple_comes_back_once_a_square_frees_up() {
+    let mut game = row_with_fruit_behind_the_apple(FruitKind::Shrink);
+    game.advance();
+    assert_eq!(None, game.fruit());
+
+    let mut events = Vec::new();
+    assert_eq!(GameStatus::InProgress, game.advance_with_events(&mut |event| events.push(event)));
+
+    let apple = game.fruit().unwrap();
+    assert_eq!(Square::Fruit(FruitKind::Apple), game.board().at(apple));
+    assert!(events.contains(&GameEvent::FruitSpawned { at: apple }));
+}
@@ -0,0 +1,148 @@
+use crate::*;
+
+use super::test_utils::*;
+
+fn parse_error(text: &str) -> LevelError {
+    Level::parse(text).unwrap_err()
+}
+
+#[test]
+fn level_describes_the_snake_from_its_head() {
+    let level = Level::parse(concat!(
+        "     \n",
+        " OO  \n",
+        "  O> \n",
+    ))
+    .unwrap();
+
+    assert_eq!(
+        vec![Location::new(3, 2), Location::new(2, 2), Location::new(2, 1), Location::new(1, 1)],
+        level.snake().collect::<Vec<_>>()
+    );
+    assert_eq!(Direction::Right, level.direction());
+}
+
+#[test]
+fn game_is_created_from_a_level() {
+    let level = Level::parse(concat!(
+        "#####\n",
+        "#   #\n",
+        "#OO^#\n",
+        "#   #\n",
+        "#####\n",
+    ))
+    .unwrap();
+
+    let mut game = Game::<{ 5 * 5 }, HardcodedNumbersGenerator>::from_level(&level, BoundaryMode::Wrap);
+
+    assert_board!(
+        game.board(),
+        &board_layout!(
+        "#####",
+        "#   #",
+        "#OOO#",
+        "#F  #",
+        "#####"
+    )
+    );
+
+    game.advance();
+
+    assert_board!(
+        game.board(),
+        &board_layout!(
+        "#####",
+        "#  O#",
+        "# OO#",
+        "#F  #",
+        "#####"
+    )
+    );
+
+    assert_eq!(GameStatus::Lost, game.advance());
+}
+
+#[test]
+fn fruit_is_placed_only_on_spawn_points() {
+    let level = Level::parse(concat!(
+        "F....\n",
+        ".....\n",
+        ".O>..\n",
+        ".....\n",
+        ".....\n",
+    ))
+    .unwrap();
+
+    let game = Game::<{ 5 * 5 }, HardcodedNumbersGenerator>::from_level(&level, BoundaryMode::Wrap);
+
+    assert_board!(
+        game.board(),
+        &board_layout!(
+        "F    ",
+        "     ",
+        " OO  ",
+        "     ",
+        "     "
+    )
+    );
+}
+
+#[test]
+fn errors_point_at_the_offending_square() {
+    assert_eq!(
+        LevelError { line: 2, column: 3, kind: LevelErrorKind::UnexpectedCharacter('x') },
+        parse_error("   \n  x\n>  ")
+    );
+    assert_eq!(
+        LevelError { line: 3, column: 3, kind: LevelErrorKind::RowLength { expected: 3, found: 2 } },
+        parse_error("   \n > \n  ")
+    );
+    assert_eq!(
+        LevelError { line: 2, column: 1, kind: LevelErrorKind::DuplicateHead },
+        parse_error("  >\n<  ")
+    );
+    assert_eq!(
+        LevelError { line: 1, column: 1, kind: LevelErrorKind::MissingHead },
+        parse_error("OO \n   ")
+    );
+    assert_eq!(
+        LevelError { line: 1, column: 1, kind: LevelErrorKind::Empty },
+        parse_error("")
+    );
+    assert_eq!(
+        LevelError { line: 1, column: 2, kind: LevelErrorKind::AmbiguousBody },
+        parse_error("OOO\n v ")
+    );
+    assert_eq!(
+        LevelError { line: 2, column: 3, kind: LevelErrorKind::DisconnectedBody },
+        parse_error("O> \n  O")
+    );
+    assert_eq!(
+        LevelError { line: 1, column: 2, kind: LevelErrorKind::HeadFacesBody },
+        parse_error("O< ")
+    );
+}
+
+#[test]
+fn error_is_displayed_with_its_position() {
+    assert_eq!("2:3: unexpected character 'x'", parse_error("   \n  x\n>  ").to_string());
+}
+
+#[test]
+fn shipped_levels_are_valid() {
+    for text in [
+        include_str!("../../../levels/box.txt"),
+        include_str!("../../../levels/pillars.txt"),
+    ] {
+        let level = Level::parse(text).unwrap();
+        Game::<{ 20 * 20 }, HardcodedNumbersGenerator>::from_level(&level, BoundaryMode::Wrap);
+    }
+    // Sized for the display of the STM32 board
+    for text in [
+        include_str!("../../../levels/box-22x16.txt"),
+        include_str!("../../../levels/pillars-22x16.txt"),
+    ] {
+        let level = Level::parse(text).unwrap();
+        Game::<{ 22 * 16 }, HardcodedNumbersGenerator>::from_level(&level, BoundaryMode::Wrap);
+    }
+}
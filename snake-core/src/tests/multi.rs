@@ -0,0 +1,169 @@
+use crate::*;
+
+use super::test_utils::*;
+
+type TwoPlayers = MultiGame<{ 12 * 5 }, HardcodedNumbersGenerator, 2>;
+
+fn advance_with_events<S: Storage, const PLAYERS: usize>(
+    game: &mut GenericMultiGame<S, HardcodedNumbersGenerator, PLAYERS>,
+) -> Vec<(Option<usize>, GameEvent)> {
+    let mut events = Vec::new();
+    game.advance_with_events(&mut |player, event| events.push((player, event)));
+    events
+}
+
+#[test]
+fn players_start_facing_each_other() {
+    let game = TwoPlayers::new(12, 5);
+
+    assert_eq!((Direction::Right, Direction::Left), (game.direction(0), game.direction(1)));
+    assert_board!(game.board(), &board_layout!(
+        "            ",
+        "   00       ",
+        "    F       ",
+        "       11   ",
+        "            "
+    ));
+}
+
+#[test]
+fn each_player_steers_its_own_snake() {
+    let mut game = TwoPlayers::new(12, 5);
+
+    game.set_direction(0, Direction::Down);
+    game.set_direction(1, Direction::Up);
+    let events = advance_with_events(&mut game);
+
+    assert_eq!(MultiStatus::InProgress, game.status());
+    assert_eq!((1, 0), (game.score(0), game.score(1)));
+    assert_eq!((3, 2), (game.length(0), game.length(1)));
+    assert!(events.contains(&(Some(0), GameEvent::FruitEaten { at: Location::new(4, 2), kind: FruitKind::Apple })));
+    assert!(events.contains(&(Some(1), GameEvent::DirectionChanged { from: Direction::Left, to: Direction::Up })));
+}
+
+#[test]
+fn heads_entering_the_same_square_kill_both_snakes() {
+    let mut game: MultiGame<7, HardcodedNumbersGenerator, 2> = GameBuilder::new(7, 1).build_multi();
+    assert_board!(game.board(), &board_layout!(" 00 11F"));
+
+    let events = advance_with_events(&mut game);
+
+    assert_eq!(MultiStatus::Finished { winner: None }, game.status());
+    assert!(events.contains(&(Some(0), GameEvent::Collided { at: Location::new(3, 0), with: Collision::Head })));
+    assert!(events.contains(&(Some(1), GameEvent::Collided { at: Location::new(3, 0), with: Collision::Head })));
+    assert_eq!(None, game.fruit());
+    assert_board!(game.board(), &board_layout!("       "));
+}
+
+#[test]
+fn snakes_swapping_heads_both_die() {
+    let mut game: MultiGame<6, HardcodedNumbersGenerator, 2> = GameBuilder::new(6, 1).build_multi();
+    assert_board!(game.board(), &board_layout!(" 0011F"));
+
+    let events = advance_with_events(&mut game);
+
+    assert_eq!(MultiStatus::Finished { winner: None }, game.status());
+    assert!(events.contains(&(Some(0), GameEvent::Collided { at: Location::new(3, 0), with: Collision::Snake })));
+    assert!(events.contains(&(Some(1), GameEvent::Collided { at: Location::new(2, 0), with: Collision::Snake })));
+}
+
+#[test]
+fn head_hitting_another_body_loses() {
+    let mut game = TwoPlayers::new(12, 5);
+
+    game.set_direction(1, Direction::Up);
+    game.advance();
+    game.advance();
+    assert_board!(game.board(), &board_layout!(
+        "            ",
+        "     001    ",
+        "    F  1    ",
+        "            ",
+        "            "
+    ));
+
+    let events = advance_with_events(&mut game);
+
+    assert_eq!(MultiStatus::Finished { winner: Some(1) }, game.status());
+    assert!(events.contains(&(Some(0), GameEvent::Collided { at: Location::new(7, 1), with: Collision::Snake })));
+    assert!(events.contains(&(Some(1), GameEvent::Won)));
+    assert!(!game.is_alive(0));
+    assert_board!(game.board(), &board_layout!(
+        "       1    ",
+        "       1    ",
+        "            ",
+        "            ",
+        "            "
+    ));
+}
+
+#[test]
+fn tail_is_an_obstacle_even_though_it_moves_away() {
+    let mut game: MultiGame<{ 6 * 3 }, HardcodedNumbersGenerator, 1> =
+        GameBuilder::new(6, 3).initial_length(4).seed(1).build_multi();
+
+    for direction in [Direction::Down, Direction::Left] {
+        game.set_direction(0, direction);
+        game.advance();
+    }
+    game.set_direction(0, Direction::Up);
+    let events = advance_with_events(&mut game);
+
+    assert_eq!(MultiStatus::Finished { winner: None }, game.status());
+    assert!(events.contains(&(Some(0), GameEvent::Collided { at: Location::new(3, 1), with: Collision::Snake })));
+}
+
+#[test]
+fn leaving_through_a_solid_edge_kills_only_that_snake() {
+    let mut game: TwoPlayers = GameBuilder::new(12, 5).boundary_mode(BoundaryMode::Solid).build_multi();
+
+    game.set_direction(0, Direction::Up);
+    game.advance();
+    let events = advance_with_events(&mut game);
+
+    assert_eq!(MultiStatus::Finished { winner: Some(1) }, game.status());
+    assert!(events.contains(&(Some(0), GameEvent::Collided { at: Location::new(4, -1), with: Collision::Boundary })));
+}
+
+#[test]
+fn paused_game_ignores_both_players() {
+    let mut game = TwoPlayers::new(12, 5);
+
+    game.pause();
+    game.set_direction(0, Direction::Down);
+
+    assert_eq!(MultiStatus::Paused, game.advance());
+    assert_eq!(0, game.ticks());
+
+    game.resume();
+    game.advance();
+    assert_eq!(Direction::Right, game.direction(0));
+}
+
+#[test]
+fn restart_brings_every_snake_back() {
+    let mut game: MultiGame<7, HardcodedNumbersGenerator, 2> = GameBuilder::new(7, 1).build_multi();
+    game.advance();
+
+    game.restart();
+
+    assert_eq!(MultiStatus::InProgress, game.status());
+    assert!(game.is_alive(0) && game.is_alive(1));
+    assert_eq!((Location::new(2, 0), Location::new(4, 0)), (game.head(0), game.head(1)));
+    assert_eq!(0, game.ticks());
+}
+
+#[test]
+fn builder_checks_the_number_of_players() {
+    assert_eq!(
+        Some(BuildError::Players { count: 0 }),
+        GameBuilder::new(6, 4).try_build_multi::<Fixed<{ 6 * 4 }>, HardcodedNumbersGenerator, 0>().err()
+    );
+    assert_eq!(
+        Some(BuildError::NoSnake),
+        GameBuilder::new(6, 4)
+            .initial_length(0)
+            .try_build_multi::<Fixed<{ 6 * 4 }>, HardcodedNumbersGenerator, 2>()
+            .err()
+    );
+}
@@ -0,0 +1,217 @@
+use crate::*;
+
+use super::test_utils::*;
+
+type TestRecorder<'a> = Recorder<'a, Fixed<{ 5 * 5 }>, HardcodedNumbersGenerator, FixedLog<32>>;
+
+fn play_a_bit(recorder: &mut impl Snake) {
+    recorder.advance();
+    recorder.advance();
+    recorder.set_direction(Direction::Up);
+    recorder.advance();
+    recorder.set_direction(Direction::Left);
+    recorder.set_direction(Direction::Down);
+    recorder.advance();
+    recorder.advance();
+}
+
+#[test]
+fn recorded_game_is_played_back_to_the_same_state() {
+    let mut recorder = TestRecorder::new(5, 5, BoundaryMode::Wrap, 3);
+    play_a_bit(&mut recorder);
+
+    let bytes = recorder.to_bytes().unwrap();
+    let replay = Replay::parse(&bytes).unwrap();
+    assert_eq!(
+        ReplayStart::New {
+            width: 5,
+            height: 5,
+            boundary_mode: BoundaryMode::Wrap,
+            seed: 3,
+            level: None,
+            fruit_policy: FruitPolicy::new(),
+        },
+        replay.start
+    );
+
+    let game = replay.play::<Fixed<{ 5 * 5 }>, HardcodedNumbersGenerator>().unwrap();
+
+    assert_eq!(recorder.ticks(), game.ticks());
+    assert_eq!(recorder.head(), game.head());
+    assert_eq!(recorder.direction(), game.direction());
+    assert_eq!(recorder.fruit(), game.fruit());
+    assert!(recorder.board().iter().eq(game.board().iter()));
+}
+
+#[test]
+fn lost_game_on_a_level_is_played_back() {
+    let level = Level::parse(concat!(
+        "#####\n",
+        "#   #\n",
+        "#OO>#\n",
+        "#  F#\n",
+        "#####\n",
+    ))
+    .unwrap();
+    let mut recorder = TestRecorder::from_level(&level, BoundaryMode::Solid, 0);
+    recorder.set_direction(Direction::Down);
+    recorder.advance();
+    recorder.advance();
+    assert_eq!(GameStatus::Lost, recorder.status());
+
+    let bytes = recorder.to_bytes().unwrap();
+    let replay = Replay::parse(&bytes).unwrap();
+    assert!(matches!(replay.start, ReplayStart::New { level: Some(text), .. } if text == level.text()));
+
+    let game = replay.play::<Fixed<{ 5 * 5 }>, HardcodedNumbersGenerator>().unwrap();
+    assert_eq!(GameStatus::Lost, game.status());
+    assert_eq!(1, game.score());
+}
+
+#[test]
+fn playback_that_ends_differently_is_reported() {
+    let mut recorder = TestRecorder::new(5, 5, BoundaryMode::Wrap, 3);
+    play_a_bit(&mut recorder);
+    let mut bytes = recorder.to_bytes().unwrap();
+
+    // Inputs follow 19 bytes of header and 7 of fruit policy, the first one turns up so make it
+    // turn left instead
+    bytes[26] ^= 0b10;
+
+    let replay = Replay::parse(&bytes).unwrap();
+    assert_eq!(
+        Err(ReplayError::Diverged),
+        replay.play::<Fixed<{ 5 * 5 }>, HardcodedNumbersGenerator>().map(|_| ())
+    );
+}
+
+#[test]
+fn invalid_replays_are_rejected() {
+    let mut recorder = TestRecorder::new(5, 5, BoundaryMode::Wrap, 3);
+    play_a_bit(&mut recorder);
+    let bytes = recorder.to_bytes().unwrap();
+
+    assert_eq!(ReplayError::Malformed, Replay::parse(&bytes[..bytes.len() - 1]).unwrap_err());
+    assert_eq!(ReplayError::Malformed, Replay::parse(b"SNAKE").unwrap_err());
+
+    let mut future = bytes.clone();
+    future[4] = 9;
+    assert_eq!(ReplayError::UnsupportedVersion(9), Replay::parse(&future).unwrap_err());
+
+    let replay = Replay::parse(&bytes).unwrap();
+    assert_eq!(
+        Err(ReplayError::SizeMismatch { width: 5, height: 5 }),
+        replay.play::<Fixed<{ 6 * 6 }>, HardcodedNumbersGenerator>().map(|_| ())
+    );
+}
+
+#[test]
+fn recorder_reports_a_full_input_log() {
+    let mut recorder = Recorder::<Fixed<{ 5 * 5 }>, HardcodedNumbersGenerator, FixedLog<2>>::new(5, 5, BoundaryMode::Wrap, 0);
+    play_a_bit(&mut recorder);
+
+    assert_eq!(Err(ReplayError::BufferFull), recorder.to_bytes());
+    assert_eq!(Direction::Down, recorder.pending_direction());
+}
+
+#[test]
+fn growable_log_keeps_every_input() {
+    let mut recorder = Recorder::<Fixed<{ 5 * 5 }>, HardcodedNumbersGenerator, Vec<u8>>::new(5, 5, BoundaryMode::Wrap, 0);
+    // Pausing and resuming leaves the game as it is, two bytes of log each time
+    for _ in 0..1000 {
+        recorder.pause();
+        recorder.resume();
+    }
+    play_a_bit(&mut recorder);
+
+    let bytes = recorder.to_bytes().unwrap();
+    assert!(bytes.len() > 2000);
+    let game = Replay::parse(&bytes).unwrap().play::<Fixed<{ 5 * 5 }>, HardcodedNumbersGenerator>().unwrap();
+    assert_eq!(recorder.head(), game.head());
+}
+
+#[test]
+fn shipped_replays_still_play_back() {
+    let replay = Replay::parse(include_bytes!("../../../replays/pillars.replay")).unwrap();
+    assert!(matches!(replay.start, ReplayStart::New { level: Some(_), .. }));
+
+    let game = replay.play::<Fixed<{ 20 * 20 }>, Pcg32>().unwrap();
+    assert_eq!(GameStatus::Lost, game.status());
+    assert_eq!((1215, 58), (game.ticks(), game.score()));
+}
+
+#[test]
+fn game_resumed_from_a_snapshot_is_played_back() {
+    let mut game = Game::<{ 5 * 5 }, HardcodedNumbersGenerator>::new(5, 5);
+    game.advance();
+    let snapshot = game.to_snapshot();
+
+    let mut recorder = TestRecorder::from_snapshot(&snapshot).unwrap();
+    play_a_bit(&mut recorder);
+
+    let bytes = recorder.to_bytes().unwrap();
+    let replay = Replay::parse(&bytes).unwrap();
+    assert_eq!(ReplayStart::Snapshot(&snapshot), replay.start);
+
+    let played = replay.play::<Fixed<{ 5 * 5 }>, HardcodedNumbersGenerator>().unwrap();
+    assert_eq!(recorder.ticks(), played.ticks());
+    assert!(recorder.board().iter().eq(played.board().iter()));
+}
+
+#[test]
+fn pauses_and_restarts_are_played_back() {
+    let level = Level::parse(concat!(
+        "#####\n",
+        "#   #\n",
+        "#OO>#\n",
+        "#  F#\n",
+        "#####\n",
+    ))
+    .unwrap();
+    let mut recorder = TestRecorder::from_level(&level, BoundaryMode::Solid, 0);
+    recorder.advance();
+    recorder.advance();
+    assert_eq!(GameStatus::Lost, recorder.status());
+
+    recorder.restart();
+    recorder.set_direction(Direction::Down);
+    recorder.pause();
+    recorder.advance();
+    recorder.set_direction(Direction::Up);
+    recorder.resume();
+    recorder.advance();
+    recorder.pause();
+
+    let bytes = recorder.to_bytes().unwrap();
+    let game = Replay::parse(&bytes)
+        .unwrap()
+        .play::<Fixed<{ 5 * 5 }>, HardcodedNumbersGenerator>()
+        .unwrap();
+
+    assert_eq!(GameStatus::Paused, game.status());
+    assert_eq!((1, Direction::Down), (game.ticks(), game.direction()));
+    assert!(recorder.board().iter().eq(game.board().iter()));
+}
+
+#[test]
+fn extra_fruits_are_played_back() {
+    let start = ReplayStart::New {
+        width: 5,
+        height: 5,
+        boundary_mode: BoundaryMode::Wrap,
+        seed: 1,
+        level: None,
+        fruit_policy: FruitPolicy::new().extra(2).chance(60),
+    };
+    let mut recorder = TestRecorder::from_start(start).unwrap();
+    play_a_bit(&mut recorder);
+    assert!(recorder.game().fruits().count() > 1);
+
+    let bytes = recorder.to_bytes().unwrap();
+    let game = Replay::parse(&bytes)
+        .unwrap()
+        .play::<Fixed<{ 5 * 5 }>, HardcodedNumbersGenerator>()
+        .unwrap();
+
+    assert!(recorder.game().fruits().eq(game.fruits()));
+}
@@ -0,0 +1,34 @@
+use crate::*;
+
+#[test]
+fn pcg32_matches_the_reference_implementation() {
+    // First numbers printed by pcg32-demo from the PCG C library
+    let mut rng = Pcg32::new(42, 54);
+    let numbers: Vec<u32> = (0..6).map(|_| rng.next()).collect();
+
+    assert_eq!(
+        vec![0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e],
+        numbers
+    );
+}
+
+#[test]
+fn pcg32_seeds_give_different_sequences() {
+    let mut a = Pcg32::from_seed(1);
+    let mut b = Pcg32::from_seed(2);
+
+    assert_ne!((a.next(), a.next()), (b.next(), b.next()));
+}
+
+#[test]
+fn pcg32_places_fruit_all_over_the_board() {
+    let mut rng = Pcg32::from_seed(DEFAULT_SEED);
+    let mut seen = [false; 22 * 16];
+
+    for _ in 0..10_000 {
+        let location = Location::new(rng.next() as i32, rng.next() as i32).wrap(22, 16);
+        seen[location.y as usize * 22 + location.x as usize] = true;
+    }
+
+    assert!(seen.iter().all(|s| *s));
+}
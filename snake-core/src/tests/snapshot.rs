@@ -0,0 +1,214 @@
+use crate::*;
+
+use super::test_utils::*;
+
+type TestGame = Game<{ 5 * 5 }, HardcodedNumbersGenerator>;
+
+fn game_in_progress() -> TestGame {
+    let level = Level::parse(concat!(
+        "#F   \n",
+        "#    \n",
+        "#OO> \n",
+        "#  F \n",
+        "#####\n",
+    ))
+    .unwrap();
+    let mut game = TestGame::from_level(&level, BoundaryMode::WrapHorizontally);
+    game.advance();
+    game.set_direction(Direction::Up);
+    game.set_direction(Direction::Left);
+    game
+}
+
+fn assert_same_game(expected: &mut TestGame, actual: &mut TestGame) {
+    for _ in 0..10 {
+        assert_eq!(expected.head(), actual.head());
+        assert_eq!(expected.direction(), actual.direction());
+        assert_eq!(expected.queued_turns(), actual.queued_turns());
+        assert!(expected.fruits().eq(actual.fruits()));
+        assert_eq!((expected.score(), expected.ticks()), (actual.score(), actual.ticks()));
+        assert_eq!(expected.length(), actual.length());
+        assert!(expected.board().iter().eq(actual.board().iter()));
+
+        assert_eq!(expected.advance(), actual.advance());
+    }
+}
+
+#[test]
+fn restored_game_continues_exactly_like_the_original() {
+    let mut game = game_in_progress();
+
+    let mut restored = TestGame::restore(&game.to_snapshot()).unwrap();
+
+    assert_same_game(&mut game, &mut restored);
+}
+
+#[test]
+fn restored_game_reports_the_whole_board_as_changed() {
+    let mut game = TestGame::restore(&game_in_progress().to_snapshot()).unwrap();
+
+    assert_eq!(13, game.changes().count());
+}
+
+#[test]
+fn snapshot_of_a_lost_game_is_restored() {
+    let mut game = TestGame::with_boundary_mode(5, 5, BoundaryMode::Solid);
+    for _ in 0..3 {
+        game.advance();
+    }
+    assert_eq!(GameStatus::Lost, game.status());
+
+    let restored = TestGame::restore(&game.to_snapshot()).unwrap();
+
+    assert_eq!(GameStatus::Lost, restored.status());
+    assert_eq!(game.score(), restored.score());
+}
+
+#[test]
+fn invalid_snapshots_are_rejected() {
+    let bytes = game_in_progress().to_snapshot();
+
+    assert_eq!(Err(SnapshotError::BufferFull), game_in_progress().snapshot(&mut [0; 8]));
+    assert_eq!(SnapshotError::Malformed, TestGame::restore(&bytes[..bytes.len() - 1]).err().unwrap());
+    assert_eq!(SnapshotError::Malformed, TestGame::restore(b"SNKR").err().unwrap());
+
+    let mut future = bytes.clone();
+    future[4] = 9;
+    assert_eq!(SnapshotError::UnsupportedVersion(9), TestGame::restore(&future).err().unwrap());
+
+    assert_eq!(
+        SnapshotError::SizeMismatch { width: 5, height: 5 },
+        Game::<{ 6 * 6 }, HardcodedNumbersGenerator>::restore(&bytes).err().unwrap()
+    );
+
+    // Segments are stored right before the board, moving the head onto a wall makes them disagree
+    let mut moved_head = bytes.clone();
+    let segments = bytes.windows(3).position(|w| w == [12, 13, 14]).unwrap();
+    moved_head[segments + 2] = 15;
+    assert_eq!(SnapshotError::Malformed, TestGame::restore(&moved_head).err().unwrap());
+}
+
+#[test]
+fn snake_across_a_wrapping_edge_is_restored() {
+    let mut game = TestGame::new(5, 5);
+    for _ in 0..3 {
+        game.advance();
+    }
+    assert_eq!(Location::new(0, 2), game.head());
+
+    let mut restored = TestGame::restore(&game.to_snapshot()).unwrap();
+    assert_same_game(&mut game, &mut restored);
+}
+
+#[test]
+fn snake_on_the_same_square_twice_is_rejected() {
+    let game: TestGame = GameBuilder::new(5, 5).start(Location::new(4, 2)).initial_length(5).build();
+    let mut bytes = game.to_snapshot();
+
+    // The head goes back onto the segment before the last one, every segment is still a move
+    // away from the previous one
+    let segments = bytes.windows(5).position(|w| w == [10, 11, 12, 13, 14]).unwrap();
+    bytes[segments + 4] = 12;
+    assert_eq!(SnapshotError::Malformed, TestGame::restore(&bytes).err().unwrap());
+}
+
+#[test]
+fn snake_with_a_gap_is_rejected() {
+    let mut bytes = game_in_progress().to_snapshot();
+
+    // The first two segments swapped, the snake still covers the same squares
+    let segments = bytes.windows(3).position(|w| w == [12, 13, 14]).unwrap();
+    bytes[segments..segments + 2].copy_from_slice(&[13, 12]);
+    assert_eq!(SnapshotError::Malformed, TestGame::restore(&bytes).err().unwrap());
+}
+
+#[test]
+fn initial_snake_on_a_wall_is_rejected() {
+    let mut bytes = game_in_progress().to_snapshot();
+
+    // The snake `restart` goes back to moved onto the bottom wall
+    let initial_snake = bytes.windows(4).rposition(|w| w == [3, 11, 12, 13]).unwrap();
+    bytes[initial_snake + 1..initial_snake + 4].copy_from_slice(&[20, 21, 22]);
+    assert_eq!(SnapshotError::Malformed, TestGame::restore(&bytes).err().unwrap());
+}
+
+#[test]
+fn squares_of_numbered_players_are_rejected() {
+    let mut bytes = game_in_progress().to_snapshot();
+
+    // The board comes right after the segments and starts with the wall in the top left corner
+    let board = bytes.windows(3).position(|w| w == [12, 13, 14]).unwrap() + 3;
+    assert_eq!([3, 1], bytes[board..board + 2]);
+    bytes[board] = 4;
+    assert_eq!(SnapshotError::Malformed, TestGame::restore(&bytes).err().unwrap());
+}
+
+#[test]
+fn turns_that_could_not_be_queued_are_rejected() {
+    let mut game = TestGame::new(5, 5);
+    game.set_direction(Direction::Up);
+    let mut bytes = game.to_snapshot();
+
+    // Left while heading right
+    bytes[10] = 2;
+
+    assert_eq!(SnapshotError::Malformed, TestGame::restore(&bytes).err().unwrap());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn game_state_goes_through_serde() {
+    let mut game = game_in_progress();
+
+    let json = serde_json::to_string(&game.to_state()).unwrap();
+    let state: GameState = serde_json::from_str(&json).unwrap();
+    let mut restored = TestGame::from_state(&state).unwrap();
+
+    assert_same_game(&mut game, &mut restored);
+}
+
+fn game_with_extra_fruits() -> TestGame {
+    let policy = FruitPolicy::new().extra(2).chance(100).lifetime(9);
+    let mut game: TestGame = GameBuilder::new(5, 5).fruit_policy(policy).build();
+    game.advance();
+    game.advance();
+    assert_eq!(3, game.fruits().count());
+    game
+}
+
+#[test]
+fn extra_fruits_are_restored() {
+    let mut game = game_with_extra_fruits();
+
+    let mut restored = TestGame::restore(&game.to_snapshot()).unwrap();
+
+    assert_eq!(game.fruit_policy(), restored.fruit_policy());
+    assert_same_game(&mut game, &mut restored);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn extra_fruits_go_through_serde() {
+    let mut game = game_with_extra_fruits();
+
+    let json = serde_json::to_string(&game.to_state()).unwrap();
+    let mut restored = TestGame::from_state(&serde_json::from_str(&json).unwrap()).unwrap();
+
+    assert_same_game(&mut game, &mut restored);
+}
+
+#[test]
+fn restored_game_restarts_with_its_initial_snake() {
+    let mut game = game_in_progress();
+    for _ in 0..3 {
+        game.advance();
+    }
+    game.pause();
+
+    let mut restored = TestGame::restore(&game.to_snapshot()).unwrap();
+    assert_eq!(GameStatus::Paused, restored.status());
+
+    game.restart();
+    restored.restart();
+    assert_same_game(&mut game, &mut restored);
+}
@@ -0,0 +1,74 @@
+use crate::*;
+
+use super::test_utils::*;
+
+#[test]
+fn constant_speed_never_changes() {
+    let speed = Speed::new(300);
+
+    assert_eq!(300, speed.interval_at(1));
+    assert_eq!(300, speed.interval_at(1000));
+}
+
+#[test]
+fn exponential_curve_stops_at_the_minimum() {
+    let speed = Speed::new(200)
+        .curve(SpeedCurve::Exponential { percent: 50 })
+        .min_interval_ms(30);
+
+    assert_eq!(
+        [200, 100, 50, 30, 30],
+        [1, 2, 3, 4, 5].map(|level| speed.interval_at(level))
+    );
+    assert_eq!(30, speed.interval_at(u32::MAX));
+}
+
+#[test]
+fn linear_curve_does_not_underflow() {
+    let speed = Speed::new(100).curve(SpeedCurve::Linear { step_ms: 40 });
+
+    assert_eq!(20, speed.interval_at(3));
+    assert_eq!(10, speed.interval_at(u32::MAX));
+}
+
+#[test]
+fn interval_never_reaches_zero() {
+    let speed = Speed::new(100)
+        .curve(SpeedCurve::Linear { step_ms: 40 })
+        .min_interval_ms(0);
+
+    assert_eq!(1, speed.interval_at(u32::MAX));
+}
+
+#[test]
+fn exponential_curve_does_not_overflow() {
+    let speed = Speed::new(u32::MAX).curve(SpeedCurve::Exponential { percent: 99 });
+
+    assert_eq!((u64::from(u32::MAX) * 99 / 100) as u32, speed.interval_at(2));
+}
+
+#[test]
+fn level_goes_up_with_the_score() {
+    // A single row, the snake passes over every square so it eats every fruit
+    let mut game = Game::<6, HardcodedNumbersGenerator>::new(6, 1);
+    let speed = Speed::new(200)
+        .curve(SpeedCurve::Linear { step_ms: 50 })
+        .per(SpeedMetric::Score, 2);
+
+    assert_eq!(1, speed.level(&game));
+
+    while game.score() < 2 {
+        game.advance();
+    }
+
+    assert_eq!(2, speed.level(&game));
+    assert_eq!(150, speed.interval_ms(&game));
+}
+
+#[test]
+fn level_can_be_counted_from_the_length() {
+    let game: Game<{ 8 * 8 }, HardcodedNumbersGenerator> = GameBuilder::new(8, 8).initial_length(5).build();
+    let speed = Speed::new(200).per(SpeedMetric::Length, 2);
+
+    assert_eq!(3, speed.level(&game));
+}
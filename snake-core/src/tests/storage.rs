@@ -0,0 +1,40 @@
+use crate::*;
+
+use super::test_utils::*;
+
+#[test]
+fn game_on_the_heap_is_sized_at_runtime() {
+    let (width, height) = (7, 3);
+    let mut game = VecGame::<HardcodedNumbersGenerator>::new(width, height);
+
+    game.advance();
+
+    assert_board!(game.board(), &board_layout!(
+        "       ",
+        "   OO  ",
+        "    F  "
+    ));
+}
+
+#[test]
+fn game_on_the_heap_plays_like_the_fixed_size_one() {
+    let mut fixed = Game::<{ 9 * 6 }, Pcg32>::with_boundary_mode(9, 6, BoundaryMode::Solid);
+    let mut heap = VecGame::<Pcg32>::with_boundary_mode(9, 6, BoundaryMode::Solid);
+
+    for direction in [Direction::Up, Direction::Left, Direction::Down, Direction::Right].iter().cycle().take(12) {
+        fixed.set_direction(*direction);
+        heap.set_direction(*direction);
+        assert_eq!(fixed.advance(), heap.advance());
+        assert!(fixed.board().iter().eq(heap.board().iter()));
+    }
+}
+
+#[test]
+fn snapshot_of_any_size_is_restored_on_the_heap() {
+    let game = Game::<{ 5 * 5 }, HardcodedNumbersGenerator>::new(5, 5);
+
+    let restored = VecGame::<HardcodedNumbersGenerator>::restore(&game.to_snapshot()).unwrap();
+
+    assert_eq!((5, 5), (restored.board().width(), restored.board().height()));
+    assert!(game.board().iter().eq(restored.board().iter()));
+}
@@ -0,0 +1,87 @@
+use std::io;
+
+use crate::*;
+
+fn game() -> VecGame<Pcg32> {
+    GameBuilder::new(8, 6).seed(3).boundary_mode(BoundaryMode::Solid).build()
+}
+
+/// Plays until the game is over, writing a frame after every tick
+fn stream_of(game: &mut VecGame<Pcg32>, keyframe_every: u32) -> (Vec<u8>, Vec<Vec<Square>>) {
+    let mut stream = StreamWriter::new(Vec::new(), 8, 6).unwrap().keyframe_every(keyframe_every);
+    let mut boards = Vec::new();
+    loop {
+        stream.write(game).unwrap();
+        boards.push(game.board().iter().map(|(_, square)| square).collect());
+        if game.status() != GameStatus::InProgress {
+            return (stream.into_inner(), boards);
+        }
+        if game.ticks() % 3 == 2 {
+            game.set_direction(Direction::Down);
+        }
+        game.advance();
+    }
+}
+
+#[test]
+fn every_frame_reads_back_as_the_board_it_was_written_from() {
+    let mut game = game();
+    let (bytes, boards) = stream_of(&mut game, 4);
+
+    let mut reader = StreamReader::new(&bytes[..]).unwrap();
+    for (tick, board) in boards.iter().enumerate() {
+        let frame = reader.next_frame().unwrap().unwrap();
+        assert_eq!(tick as u32, frame.tick);
+        assert!(reader.board().iter().map(|(_, square)| square).eq(board.iter().copied()));
+    }
+    assert!(reader.next_frame().unwrap().is_none());
+    assert_eq!(GameStatus::Lost, game.status());
+}
+
+#[test]
+fn keyframes_come_every_so_often() {
+    let (bytes, boards) = stream_of(&mut game(), 4);
+    let frames: Vec<Frame> = StreamReader::new(&bytes[..]).unwrap().map(Result::unwrap).collect();
+
+    assert_eq!(boards.len(), frames.len());
+    assert!(frames.iter().enumerate().all(|(i, frame)| frame.keyframe == (i % 4 == 0)));
+    // A tick moves the head and the tail of the snake
+    assert_eq!(2, frames[1].changes.len());
+    assert_eq!(GameStatus::Lost, frames.last().unwrap().status);
+}
+
+#[test]
+fn frames_keep_the_score_and_time() {
+    let mut game = game();
+    let (bytes, _) = stream_of(&mut game, 100);
+    let frames: Vec<Frame> = StreamReader::new(&bytes[..]).unwrap().map(Result::unwrap).collect();
+
+    assert_eq!(game.score(), frames.last().unwrap().score);
+    assert!(frames.windows(2).all(|pair| pair[0].millis <= pair[1].millis));
+}
+
+#[test]
+fn games_of_another_size_are_not_written() {
+    let mut stream = StreamWriter::new(Vec::new(), 5, 5).unwrap();
+    let error = stream.write(&game()).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidInput, error.kind());
+}
+
+#[test]
+fn broken_streams_are_rejected() {
+    let (bytes, _) = stream_of(&mut game(), 4);
+    let invalid = |bytes: &[u8]| {
+        let error = StreamReader::new(bytes).and_then(|reader| reader.collect::<io::Result<Vec<_>>>());
+        error.unwrap_err().kind()
+    };
+
+    assert_eq!(io::ErrorKind::InvalidData, invalid(b"SNKS\x01\x08\x06"));
+    assert_eq!(io::ErrorKind::InvalidData, invalid(b"SNKT\x09\x08\x06"));
+    assert_eq!(io::ErrorKind::InvalidData, invalid(b"SNKT\x01\x00\x06"));
+    assert_eq!(io::ErrorKind::UnexpectedEof, invalid(&bytes[..bytes.len() - 1]));
+
+    // The first frame turned into a delta
+    let mut delta_first = bytes.clone();
+    delta_first[8] = 1;
+    assert_eq!(io::ErrorKind::InvalidData, invalid(&delta_first));
+}
@@ -7,11 +7,26 @@ pub struct HardcodedNumbersGenerator {
 }
 
 impl RandomNumberGenerator for HardcodedNumbersGenerator {
+    fn from_seed(seed: u64) -> Self {
+        HardcodedNumbersGenerator {
+            current: (seed % 6) as usize,
+            ..Default::default()
+        }
+    }
+
     fn next(&mut self) -> u32 {
         let result = self.numbers[self.current];
         self.current = (self.current + 1) % self.numbers.len();
         result
     }
+
+    fn state(&self) -> u128 {
+        self.current as u128
+    }
+
+    fn from_state(state: u128) -> Self {
+        Self::from_seed(state as u64)
+    }
 }
 
 impl Default for HardcodedNumbersGenerator {
@@ -26,13 +41,7 @@ impl Default for HardcodedNumbersGenerator {
 #[macro_export]
 macro_rules! board_layout {
     ( $( $x:expr ),* ) => {
-        {
-            let mut temp_vec = Vec::new();
-            $(
-                temp_vec.push(String::from($x));
-            )*
-            temp_vec
-        }
+        Vec::from([$( String::from($x) ),*])
     }; }
 
 #[macro_export]
@@ -50,7 +59,7 @@ macro_rules! assert_board {
     }};
 }
 
-pub fn expected_to_string(expected: &Vec<String>) -> String {
+pub fn expected_to_string(expected: &[String]) -> String {
     let mut result = String::new();
     for e in expected {
         writeln!(result, "\"{}\"", e).unwrap();
@@ -71,8 +80,13 @@ pub fn board_to_string(board: &dyn Board) -> String {
                     y: y as i32
                 }) {
                     Square::Snake => 'O',
-                    Square::Fruit => 'F',
+                    Square::Fruit(FruitKind::Apple) => 'F',
+                    Square::Fruit(FruitKind::Bonus) => '$',
+                    Square::Fruit(FruitKind::Feast) => '%',
+                    Square::Fruit(FruitKind::Shrink) => '-',
                     Square::Empty => ' ',
+                    Square::Wall => '#',
+                    Square::Player(n) => char::from_digit(n.into(), 10).unwrap_or('?'),
                 }
             )
             .unwrap();
@@ -82,13 +96,13 @@ pub fn board_to_string(board: &dyn Board) -> String {
     result
 }
 
-pub fn check_board(board: &dyn Board, expected: &Vec<String>) -> Vec<String> {
+pub fn check_board(board: &dyn Board, expected: &[String]) -> Vec<String> {
     assert_eq!(board.height(), expected.len(), "Invalid height");
 
     expected
         .iter()
         .enumerate()
-        .map(|(y, row)| -> Vec<String> {
+        .flat_map(|(y, row)| -> Vec<String> {
             assert_eq!(board.width(), row.chars().count(), "Invalid width");
 
             row.chars()
@@ -96,7 +110,12 @@ pub fn check_board(board: &dyn Board, expected: &Vec<String>) -> Vec<String> {
                 .map(|(x, square)| {
                     let expected = match square {
                         'O' => Square::Snake,
-                        'F' => Square::Fruit,
+                        'F' => Square::Fruit(FruitKind::Apple),
+                        '$' => Square::Fruit(FruitKind::Bonus),
+                        '%' => Square::Fruit(FruitKind::Feast),
+                        '-' => Square::Fruit(FruitKind::Shrink),
+                        '#' => Square::Wall,
+                        '0'..='9' => Square::Player(square as u8 - b'0'),
                         _ => Square::Empty,
                     };
 
@@ -122,6 +141,5 @@ pub fn check_board(board: &dyn Board, expected: &Vec<String>) -> Vec<String> {
                 .filter_map(Result::err)
                 .collect()
         })
-        .flatten()
         .collect()
 }
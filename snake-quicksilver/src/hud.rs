@@ -0,0 +1,79 @@
+use quicksilver::{
+    geom::Rectangle,
+    graphics::{Background::Col, Color},
+    lifecycle::Window,
+};
+
+const PIXEL: i32 = 4;
+
+/// Labels the HUD prints, every character in them needs a glyph
+pub const LEVEL: &str = "LEVEL";
+pub const SCORE: &str = "SCORE";
+pub const LENGTH: &str = "LENGTH";
+pub const PAUSED: &str = "PAUSED";
+pub const DEMO: &str = "DEMO";
+
+/// 3x5 pixel glyphs, each row is three bits with the leftmost pixel in the highest bit
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' | 'O' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' | 'S' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'C' => [0b111, 0b100, 0b100, 0b100, 0b111],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'G' => [0b111, 0b100, 0b101, 0b101, 0b111],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'P' => [0b111, 0b101, 0b111, 0b100, 0b100],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        _ => [0; 5],
+    }
+}
+
+/// Draws `text` with its top left corner at `(x, y)`
+pub fn draw_text(window: &mut Window, x: i32, y: i32, text: &str, color: Color) {
+    for (i, c) in text.chars().enumerate() {
+        let left = x + i as i32 * 4 * PIXEL;
+
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 {
+                    window.draw(
+                        &Rectangle::new(
+                            (left + column * PIXEL, y + row as i32 * PIXEL),
+                            (PIXEL, PIXEL),
+                        ),
+                        Col(color),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_label_can_be_drawn() {
+        let labels = [LEVEL, SCORE, LENGTH, PAUSED, DEMO, "0123456789"];
+        for c in labels.iter().flat_map(|label| label.chars()) {
+            assert_ne!([0; 5], glyph(c), "no glyph for '{}'", c);
+        }
+    }
+}
@@ -1,5 +1,4 @@
 // Draw some multi-colored geometry to the screen
-use rand;
 use snake::*;
 
 use quicksilver::{
@@ -10,17 +9,71 @@ use quicksilver::{
     Result,
 };
 
-struct SnakeQuicksilver(Box<dyn Snake>);
+mod hud;
+
+/// The autopilot is there while the demo mode is on, the last fields are the indices in
+/// `LEVELS` and `BOUNDARY_MODES`
+struct SnakeQuicksilver(
+    Box<dyn Snake>,
+    Speed,
+    Option<Greedy<Fixed<{ 20 * 20 }>>>,
+    usize,
+    usize,
+);
+
+/// Levels cycled through with L, the first one is the open board
+const LEVELS: [Option<&str>; 3] = [
+    None,
+    Some(include_str!("../../levels/box.txt")),
+    Some(include_str!("../../levels/pillars.txt")),
+];
+
+/// Board edges cycled through with B
+const BOUNDARY_MODES: [BoundaryMode; 4] = [
+    BoundaryMode::Wrap,
+    BoundaryMode::Solid,
+    BoundaryMode::WrapHorizontally,
+    BoundaryMode::WrapVertically,
+];
+
+fn new_game(level: usize, boundary_mode: usize) -> Box<dyn Snake> {
+    let seed = rand::random();
+    let level = LEVELS[level].map(|text| Level::parse(text).expect("bundled levels are valid"));
+    Box::new(create_game_instance!(20, 20, Pcg32, |b| {
+        let b = b
+            .seed(seed)
+            .fruit_policy(FruitPolicy::mixed())
+            .boundary_mode(BOUNDARY_MODES[boundary_mode]);
+        match &level {
+            Some(level) => b.level(level),
+            None => b,
+        }
+    }))
+}
+
+fn fruit_color(kind: FruitKind) -> Color {
+    match kind {
+        FruitKind::Apple => Color::GREEN,
+        FruitKind::Bonus => Color::YELLOW,
+        FruitKind::Feast => Color::ORANGE,
+        FruitKind::Shrink => Color::PURPLE,
+    }
+}
 
 impl State for SnakeQuicksilver {
     fn new() -> Result<SnakeQuicksilver> {
-        Ok(SnakeQuicksilver(Box::new(create_game_instance!(
-            20, 20, RNG
-        ))))
+        Ok(SnakeQuicksilver(new_game(0, 0), Speed::default(), None, 0, 0))
     }
 
-    fn update(&mut self, _window: &mut Window) -> Result<()> {
+    fn update(&mut self, window: &mut Window) -> Result<()> {
+        if let Some(autopilot) = &mut self.2 {
+            if matches!(self.0.status(), GameStatus::Lost | GameStatus::Won) {
+                self.0.restart();
+            }
+            autopilot.steer(&mut *self.0);
+        }
         self.0.advance();
+        window.set_update_rate(self.1.interval_ms(&*self.0).into());
         Ok(())
     }
 
@@ -30,10 +83,40 @@ impl State for SnakeQuicksilver {
             Event::Key(Key::Right, ButtonState::Pressed) => Some(Direction::Right),
             Event::Key(Key::Up, ButtonState::Pressed) => Some(Direction::Up),
             Event::Key(Key::Down, ButtonState::Pressed) => Some(Direction::Down),
+            Event::Key(Key::P, ButtonState::Pressed) => {
+                match self.0.status() {
+                    GameStatus::Paused => self.0.resume(),
+                    _ => self.0.pause(),
+                }
+                None
+            }
+            Event::Key(Key::R, ButtonState::Pressed) => {
+                self.0.restart();
+                None
+            }
+            Event::Key(Key::L, ButtonState::Pressed) => {
+                self.3 = (self.3 + 1) % LEVELS.len();
+                self.0 = new_game(self.3, self.4);
+                None
+            }
+            Event::Key(Key::B, ButtonState::Pressed) => {
+                self.4 = (self.4 + 1) % BOUNDARY_MODES.len();
+                self.0 = new_game(self.3, self.4);
+                None
+            }
+            Event::Key(Key::D, ButtonState::Pressed) => {
+                self.2 = match self.2 {
+                    Some(_) => None,
+                    None => Some(Greedy::new(20, 20)),
+                };
+                None
+            }
             _ => None,
         };
 
         if let Some(d) = direction {
+            // Steering takes over from the demo
+            self.2 = None;
             self.0.set_direction(d);
         }
 
@@ -45,32 +128,55 @@ impl State for SnakeQuicksilver {
 
         for (Location { x, y }, s) in self.0.board().iter() {
             match s {
-                snake::Square::Snake => window.draw(
+                snake::Square::Snake | snake::Square::Player(_) => window.draw(
                     &Rectangle::new(((x * 20) as i32, (y * 20) as i32), (20, 20)),
                     Col(Color::BLUE),
                 ),
-                snake::Square::Fruit => window.draw(
+                snake::Square::Fruit(kind) => window.draw(
+                    &Rectangle::new(((x * 20) as i32, (y * 20) as i32), (20, 20)),
+                    Col(fruit_color(kind)),
+                ),
+                snake::Square::Wall => window.draw(
                     &Rectangle::new(((x * 20) as i32, (y * 20) as i32), (20, 20)),
-                    Col(Color::GREEN),
+                    Col(Color::BLACK),
                 ),
                 snake::Square::Empty => {}
             }
         }
-        Ok(())
-    }
-}
 
-#[derive(Default)]
-pub struct RNG {}
+        // Solid edges are drawn as lines along the board
+        let (wrap_x, wrap_y) = self.0.boundary_mode().wraps();
+        let edge = Col(Color::from_rgba(128, 128, 128, 1.0));
+        if !wrap_x {
+            window.draw(&Rectangle::new((0, 0), (2, 400)), edge);
+            window.draw(&Rectangle::new((398, 0), (2, 400)), edge);
+        }
+        if !wrap_y {
+            window.draw(&Rectangle::new((0, 0), (400, 2)), edge);
+            window.draw(&Rectangle::new((0, 398), (400, 2)), edge);
+        }
+
+        let color = match self.0.status() {
+            GameStatus::Lost => Color::RED,
+            GameStatus::Paused => Color::from_rgba(128, 128, 128, 1.0),
+            _ => Color::BLACK,
+        };
+        let level = self.1.level(&*self.0);
+        hud::draw_text(window, 420, 20, &format!("{} {}", hud::LEVEL, level), color);
+        hud::draw_text(window, 420, 60, &format!("{} {}", hud::SCORE, self.0.score()), color);
+        hud::draw_text(window, 420, 100, &format!("{} {}", hud::LENGTH, self.0.length()), color);
+        if self.0.status() == GameStatus::Paused {
+            hud::draw_text(window, 420, 140, hud::PAUSED, color);
+        } else if self.2.is_some() {
+            hud::draw_text(window, 420, 140, hud::DEMO, color);
+        }
 
-impl snake::RandomNumberGenerator for RNG {
-    fn next(&mut self) -> u32 {
-        rand::random::<u32>()
+        Ok(())
     }
 }
 
 fn main() {
     let mut settings = Settings::default();
-    settings.update_rate = 200.0;
+    settings.update_rate = Speed::default().interval_at(1).into();
     run::<SnakeQuicksilver>("Snake Quicksilver", Vector::new(800, 600), settings);
 }
@@ -0,0 +1,53 @@
+//! Multiplayer snake over TCP.
+//!
+//! The server owns the game and ticks it, clients only send where they want to turn and draw
+//! what the server tells them. Every message is a line of text ending with `\n`.
+//!
+//! A client connects and sends
+//!
+//! ```text
+//! JOIN <name>                  the first line, names can't contain whitespace
+//! TURN up|down|left|right      at any time after WELCOME
+//! QUIT                         leaves, so does closing the connection
+//! ```
+//!
+//! and the server answers with
+//!
+//! ```text
+//! WELCOME <player> <players> <width> <height>
+//! PLAYER <player> <name>       for everyone who joined so far and whoever joins later
+//! START                        once everyone joined and after every game over
+//! TICK <tick> <x>,<y>,<square> ...
+//! SCORE <player> <score>
+//! DEAD <player>
+//! OVER <player>|draw
+//! BYE <reason>                 before closing the connection, e.g. "full" or "expected JOIN"
+//! ```
+//!
+//! `TICK` lists the squares that changed since the previous one. The first one after `START`
+//! lists every square that isn't empty, so the client starts from an empty board. Squares are
+//! written as in levels: `.` empty, `#` wall, `F` apple, `$` bonus, `%` feast, `-` shrink, and
+//! player `n`'s snake is `n` as a hexadecimal digit.
+//!
+//! ```text
+//! > JOIN ann
+//! < WELCOME 0 2 40 20
+//! < PLAYER 0 ann
+//! < PLAYER 1 bob
+//! < START
+//! < TICK 0 10,6,0 11,6,0 3,13,F 28,13,1 29,13,1
+//! > TURN down
+//! < TICK 1 10,6,. 11,7,0 27,13,1 29,13,.
+//! ```
+
+mod protocol;
+mod server;
+
+pub use protocol::{parse_square, square_char, ClientMessage, ProtocolError, ServerMessage};
+pub use server::{Config, Server};
+
+#[cfg(test)]
+mod tests {
+    mod loopback;
+    mod protocol;
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use snake::BoundaryMode;
+use snake_server::{Config, Server};
+
+fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|a| a != name).nth(1)
+}
+
+fn fail(message: std::fmt::Arguments) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+fn boundary_mode_from_args() -> BoundaryMode {
+    match arg_value("--boundary").as_deref() {
+        None | Some("wrap") => BoundaryMode::Wrap,
+        Some("solid") => BoundaryMode::Solid,
+        Some("horizontal") => BoundaryMode::WrapHorizontally,
+        Some("vertical") => BoundaryMode::WrapVertically,
+        Some(other) => fail(format_args!(
+            "Unknown boundary mode '{}', expected wrap, solid, horizontal or vertical",
+            other
+        )),
+    }
+}
+
+fn config_from_args() -> Config {
+    let mut config = Config {
+        boundary_mode: boundary_mode_from_args(),
+        seed: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |t| t.as_nanos() as u64),
+        ..Config::default()
+    };
+
+    if let Some(size) = arg_value("--size") {
+        match size
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+        {
+            Some((width, height)) => (config.width, config.height) = (width, height),
+            None => fail(format_args!("Invalid board size '{}', expected WIDTHxHEIGHT", size)),
+        }
+    }
+    if let Some(interval) = arg_value("--interval") {
+        match interval.parse() {
+            Ok(ms) => config.interval = Duration::from_millis(ms),
+            Err(_) => fail(format_args!("Invalid interval '{}', expected milliseconds", interval)),
+        }
+    }
+    config
+}
+
+fn serve<const PLAYERS: usize>(address: &str, config: Config) {
+    let server = Server::<PLAYERS>::bind(address, config)
+        .unwrap_or_else(|e| fail(format_args!("Can't start the server on {}: {}", address, e)));
+    println!(
+        "Waiting for {} players on {}",
+        PLAYERS,
+        server.local_addr().map_or(address.into(), |a| a.to_string())
+    );
+    server
+        .run()
+        .unwrap_or_else(|e| fail(format_args!("The server stopped: {}", e)));
+}
+
+/// Hosts games until every player left, see the crate documentation for the protocol
+fn main() {
+    let address = arg_value("--listen").unwrap_or_else(|| "0.0.0.0:7878".into());
+    let config = config_from_args();
+
+    match arg_value("--players").as_deref() {
+        None | Some("2") => serve::<2>(&address, config),
+        Some("3") => serve::<3>(&address, config),
+        Some("4") => serve::<4>(&address, config),
+        Some(other) => fail(format_args!("Unsupported number of players '{}', expected 2 to 4", other)),
+    }
+}
@@ -0,0 +1,220 @@
+//! Messages going back and forth between the server and its clients, one per line.
+
+use std::fmt;
+use std::str::FromStr;
+
+use snake::{Direction, FruitKind, Location, Square, MAX_PLAYERS};
+
+/// A line the server could not make sense of, or a client sent out of turn
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ProtocolError(pub String);
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid message '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum ClientMessage {
+    /// The first line of every client, names can't contain whitespace
+    Join { name: String },
+    Turn(Direction),
+    Quit,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum ServerMessage {
+    /// Answers `Join`, the client steers snake `player` on a board of `width` by `height`
+    Welcome {
+        player: usize,
+        players: usize,
+        width: usize,
+        height: usize,
+    },
+    /// Player `player` joined under `name`, sent for everyone who joined before too
+    Player { player: usize, name: String },
+    /// A game starts, the board is empty until the next `Tick`
+    Start,
+    /// Squares that changed since the previous `Tick`, the first one after `Start` has every
+    /// square that isn't empty
+    Tick { tick: u32, changes: Vec<(Location, Square)> },
+    Score { player: usize, score: u32 },
+    Dead { player: usize },
+    /// The game is over, `None` when nobody won
+    Over { winner: Option<usize> },
+    /// The server closes the connection
+    Bye { reason: String },
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "up",
+        Direction::Down => "down",
+        Direction::Left => "left",
+        Direction::Right => "right",
+    }
+}
+
+/// Players are hexadecimal digits, the other squares are drawn as in levels and the terminal
+pub fn square_char(square: Square) -> char {
+    match square {
+        Square::Empty => '.',
+        Square::Wall => '#',
+        Square::Snake => 'O',
+        Square::Fruit(FruitKind::Apple) => 'F',
+        Square::Fruit(FruitKind::Bonus) => '$',
+        Square::Fruit(FruitKind::Feast) => '%',
+        Square::Fruit(FruitKind::Shrink) => '-',
+        Square::Player(n) => char::from_digit(n.into(), MAX_PLAYERS as u32).unwrap_or('?'),
+    }
+}
+
+pub fn parse_square(c: char) -> Option<Square> {
+    Some(match c {
+        '.' => Square::Empty,
+        '#' => Square::Wall,
+        'O' => Square::Snake,
+        'F' => Square::Fruit(FruitKind::Apple),
+        '$' => Square::Fruit(FruitKind::Bonus),
+        '%' => Square::Fruit(FruitKind::Feast),
+        '-' => Square::Fruit(FruitKind::Shrink),
+        c => Square::Player(c.to_digit(MAX_PLAYERS as u32)? as u8),
+    })
+}
+
+impl fmt::Display for ClientMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientMessage::Join { name } => write!(f, "JOIN {}", name),
+            ClientMessage::Turn(direction) => write!(f, "TURN {}", direction_name(*direction)),
+            ClientMessage::Quit => write!(f, "QUIT"),
+        }
+    }
+}
+
+impl fmt::Display for ServerMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerMessage::Welcome {
+                player,
+                players,
+                width,
+                height,
+            } => write!(f, "WELCOME {} {} {} {}", player, players, width, height),
+            ServerMessage::Player { player, name } => write!(f, "PLAYER {} {}", player, name),
+            ServerMessage::Start => write!(f, "START"),
+            ServerMessage::Tick { tick, changes } => {
+                write!(f, "TICK {}", tick)?;
+                for (Location { x, y }, square) in changes {
+                    write!(f, " {},{},{}", x, y, square_char(*square))?;
+                }
+                Ok(())
+            }
+            ServerMessage::Score { player, score } => write!(f, "SCORE {} {}", player, score),
+            ServerMessage::Dead { player } => write!(f, "DEAD {}", player),
+            ServerMessage::Over { winner: Some(player) } => write!(f, "OVER {}", player),
+            ServerMessage::Over { winner: None } => write!(f, "OVER draw"),
+            ServerMessage::Bye { reason } => write!(f, "BYE {}", reason),
+        }
+    }
+}
+
+fn number<T: FromStr>(word: Option<&str>) -> Option<T> {
+    word?.parse().ok()
+}
+
+fn name(word: Option<&str>) -> Option<String> {
+    word.filter(|w| !w.is_empty()).map(String::from)
+}
+
+fn change(word: &str) -> Option<(Location, Square)> {
+    let mut parts = word.splitn(3, ',');
+    let x = number(parts.next())?;
+    let y = number(parts.next())?;
+    let mut square = parts.next()?.chars();
+    match (square.next(), square.next()) {
+        (Some(c), None) => Some((Location { x, y }, parse_square(c)?)),
+        _ => None,
+    }
+}
+
+impl FromStr for ClientMessage {
+    type Err = ProtocolError;
+
+    fn from_str(line: &str) -> Result<Self, ProtocolError> {
+        let mut words = line.split_whitespace();
+        let message = match words.next() {
+            Some("JOIN") => name(words.next()).map(|name| ClientMessage::Join { name }),
+            Some("TURN") => match words.next() {
+                Some("up") => Some(ClientMessage::Turn(Direction::Up)),
+                Some("down") => Some(ClientMessage::Turn(Direction::Down)),
+                Some("left") => Some(ClientMessage::Turn(Direction::Left)),
+                Some("right") => Some(ClientMessage::Turn(Direction::Right)),
+                _ => None,
+            },
+            Some("QUIT") => Some(ClientMessage::Quit),
+            _ => None,
+        };
+
+        match (message, words.next()) {
+            (Some(message), None) => Ok(message),
+            _ => Err(ProtocolError(line.into())),
+        }
+    }
+}
+
+impl FromStr for ServerMessage {
+    type Err = ProtocolError;
+
+    fn from_str(line: &str) -> Result<Self, ProtocolError> {
+        let mut words = line.split_whitespace();
+        let message = match words.next() {
+            Some("WELCOME") => (|| {
+                Some(ServerMessage::Welcome {
+                    player: number(words.next())?,
+                    players: number(words.next())?,
+                    width: number(words.next())?,
+                    height: number(words.next())?,
+                })
+            })(),
+            Some("PLAYER") => (|| {
+                Some(ServerMessage::Player {
+                    player: number(words.next())?,
+                    name: name(words.next())?,
+                })
+            })(),
+            Some("START") => Some(ServerMessage::Start),
+            Some("TICK") => (|| {
+                let tick = number(words.next())?;
+                let changes = words.by_ref().map(change).collect::<Option<_>>()?;
+                Some(ServerMessage::Tick { tick, changes })
+            })(),
+            Some("SCORE") => (|| {
+                Some(ServerMessage::Score {
+                    player: number(words.next())?,
+                    score: number(words.next())?,
+                })
+            })(),
+            Some("DEAD") => number(words.next()).map(|player| ServerMessage::Dead { player }),
+            Some("OVER") => match words.next() {
+                Some("draw") => Some(ServerMessage::Over { winner: None }),
+                word => number(word).map(|player| ServerMessage::Over { winner: Some(player) }),
+            },
+            // The reason is the rest of the line
+            Some("BYE") => {
+                let reason = line.trim().strip_prefix("BYE").unwrap_or_default().trim().to_string();
+                words = "".split_whitespace();
+                Some(ServerMessage::Bye { reason })
+            }
+            _ => None,
+        };
+
+        match (message, words.next()) {
+            (Some(message), None) => Ok(message),
+            _ => Err(ProtocolError(line.into())),
+        }
+    }
+}
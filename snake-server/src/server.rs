@@ -0,0 +1,280 @@
+//! The server waits for every player to join, then plays games until everyone left.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use snake::{BoundaryMode, Dynamic, GameBuilder, MultiStatus, Pcg32, VecMultiGame};
+
+use crate::protocol::{ClientMessage, ServerMessage};
+
+/// How long a new connection has to send `JOIN`
+const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Config {
+    pub width: usize,
+    pub height: usize,
+    pub boundary_mode: BoundaryMode,
+    pub seed: u64,
+    /// Time between ticks
+    pub interval: Duration,
+    /// Time between the end of a game and the start of the next one
+    pub restart_after: Duration,
+    /// Number of games to play before `run` returns, `None` to go on until everyone left
+    pub rounds: Option<u32>,
+    /// How long the game waits on a client that doesn't read what it's sent before dropping it
+    pub write_timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: 40,
+            height: 20,
+            boundary_mode: BoundaryMode::Wrap,
+            seed: 0,
+            interval: Duration::from_millis(snake::Speed::default().interval_at(1).into()),
+            restart_after: Duration::from_secs(3),
+            rounds: None,
+            write_timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Hosts a game for `PLAYERS` players, it's the only one moving the snakes and clients only
+/// send their turns
+pub struct Server<const PLAYERS: usize> {
+    listener: TcpListener,
+    config: Config,
+}
+
+struct Client {
+    name: String,
+    /// `None` once the client left or its connection broke
+    stream: Option<TcpStream>,
+}
+
+impl<const PLAYERS: usize> Server<PLAYERS> {
+    /// Listens on `address`, fails when the game can't be built for the configuration
+    pub fn bind(address: impl ToSocketAddrs, config: Config) -> io::Result<Self> {
+        Self::game(&config)?;
+        Ok(Server {
+            listener: TcpListener::bind(address)?,
+            config,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    fn game(config: &Config) -> io::Result<VecMultiGame<Pcg32, PLAYERS>> {
+        GameBuilder::new(config.width, config.height)
+            .seed(config.seed)
+            .boundary_mode(config.boundary_mode)
+            .try_build_multi::<Dynamic, Pcg32, PLAYERS>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+    }
+
+    /// Plays until every player left or the configured number of games was played
+    pub fn run(self) -> io::Result<()> {
+        let mut game = Self::game(&self.config)?;
+        let (width, height) = (game.board().width(), game.board().height());
+        let (joined, new_clients) = mpsc::channel();
+        let (input, inputs) = mpsc::channel();
+
+        let listener = self.listener;
+        let write_timeout = self.config.write_timeout;
+        thread::spawn(move || accept::<PLAYERS>(listener, (width, height), write_timeout, joined, input));
+
+        let mut clients = Vec::with_capacity(PLAYERS);
+        while clients.len() < PLAYERS {
+            let (name, stream) = new_clients.recv().map_err(|_| io::ErrorKind::BrokenPipe)?;
+            clients.push(Client {
+                name,
+                stream: Some(stream),
+            });
+            // The new player learns about everyone so far, the others only about the new one
+            let player = clients.len() - 1;
+            let names: Vec<ServerMessage> = clients
+                .iter()
+                .enumerate()
+                .map(|(player, client)| ServerMessage::Player {
+                    player,
+                    name: client.name.clone(),
+                })
+                .collect();
+            for message in &names {
+                send(&mut clients[player..], message);
+            }
+            send(&mut clients[..player], &names[player]);
+        }
+
+        let mut round = 0;
+        loop {
+            play(&mut game, &mut clients, &inputs, self.config.interval);
+            round += 1;
+            if self.config.rounds.is_some_and(|rounds| round >= rounds) || clients.iter().all(|c| c.stream.is_none()) {
+                return Ok(());
+            }
+            thread::sleep(self.config.restart_after);
+            game.restart();
+        }
+    }
+}
+
+/// Accepts connections and leaves the handshake to a thread for each, so that a connection
+/// that doesn't send `JOIN` doesn't hold up the players after it
+fn accept<const PLAYERS: usize>(
+    listener: TcpListener,
+    size: (usize, usize),
+    write_timeout: Duration,
+    joined: Sender<(String, TcpStream)>,
+    input: Sender<(usize, ClientMessage)>,
+) {
+    let players = Arc::new(Mutex::new(0));
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let (players, joined, input) = (players.clone(), joined.clone(), input.clone());
+        thread::spawn(move || join::<PLAYERS>(stream, size, write_timeout, &players, joined, input));
+    }
+}
+
+/// Welcomes the player on `stream` unless the game is full, then passes the lines it sends on
+/// to `input`
+fn join<const PLAYERS: usize>(
+    mut stream: TcpStream,
+    (width, height): (usize, usize),
+    write_timeout: Duration,
+    players: &Mutex<usize>,
+    joined: Sender<(String, TcpStream)>,
+    input: Sender<(usize, ClientMessage)>,
+) {
+    stream.set_nodelay(true).ok();
+    stream.set_read_timeout(Some(JOIN_TIMEOUT)).ok();
+    stream.set_write_timeout(Some(write_timeout)).ok();
+    let Ok(mut reader) = stream.try_clone().map(BufReader::new) else {
+        return;
+    };
+
+    let mut line = String::new();
+    reader.read_line(&mut line).ok();
+    // Players are numbered in the order they're passed on to `joined`
+    let mut players = players.lock().unwrap();
+    let name = match line.trim().parse() {
+        _ if *players == PLAYERS => {
+            writeln!(stream, "{}", ServerMessage::Bye { reason: "full".into() }).ok();
+            return;
+        }
+        Ok(ClientMessage::Join { name }) => name,
+        _ => {
+            let reason = "expected JOIN".into();
+            writeln!(stream, "{}", ServerMessage::Bye { reason }).ok();
+            return;
+        }
+    };
+
+    let player = *players;
+    let welcome = ServerMessage::Welcome {
+        player,
+        players: PLAYERS,
+        width,
+        height,
+    };
+    if writeln!(stream, "{}", welcome).is_err() {
+        return;
+    }
+    stream.set_read_timeout(None).ok();
+    *players += 1;
+    if joined.send((name, stream)).is_err() {
+        return;
+    }
+    drop(players);
+
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        // Lines that don't parse are dropped, the player just doesn't turn
+        if let Ok(message) = line.trim().parse() {
+            if input.send((player, message)).is_err() {
+                return;
+            }
+        }
+    }
+    input.send((player, ClientMessage::Quit)).ok();
+}
+
+/// Drops the clients the message can't be written to, a write that timed out may have left
+/// half a line behind so their connection is closed too
+fn send(clients: &mut [Client], message: &ServerMessage) {
+    let line = format!("{}\n", message);
+    for client in clients {
+        if let Some(stream) = &mut client.stream {
+            if stream.write_all(line.as_bytes()).is_err() {
+                stream.shutdown(Shutdown::Both).ok();
+                client.stream = None;
+            }
+        }
+    }
+}
+
+fn tick<const PLAYERS: usize>(game: &mut VecMultiGame<Pcg32, PLAYERS>) -> ServerMessage {
+    ServerMessage::Tick {
+        tick: game.ticks(),
+        changes: game.changes().map(|change| (change.location, change.new)).collect(),
+    }
+}
+
+/// Plays one game from the start to the end
+fn play<const PLAYERS: usize>(
+    game: &mut VecMultiGame<Pcg32, PLAYERS>,
+    clients: &mut [Client],
+    inputs: &Receiver<(usize, ClientMessage)>,
+    interval: Duration,
+) {
+    game.reset_changes();
+    send(clients, &ServerMessage::Start);
+    send(clients, &tick(game));
+
+    loop {
+        thread::sleep(interval);
+        while let Ok((player, message)) = inputs.try_recv() {
+            match message {
+                ClientMessage::Turn(direction) => game.set_direction(player, direction),
+                ClientMessage::Quit => {
+                    send(&mut clients[player..=player], &ServerMessage::Bye { reason: "quit".into() });
+                    clients[player].stream = None;
+                }
+                ClientMessage::Join { .. } => {}
+            }
+        }
+
+        let before: [(u32, bool); PLAYERS] = core::array::from_fn(|p| (game.score(p), game.is_alive(p)));
+        let status = game.advance();
+        send(clients, &tick(game));
+        for (player, (score, alive)) in before.into_iter().enumerate() {
+            if game.score(player) != score {
+                let score = game.score(player);
+                send(clients, &ServerMessage::Score { player, score });
+            }
+            if alive && !game.is_alive(player) {
+                send(clients, &ServerMessage::Dead { player });
+            }
+        }
+
+        if let MultiStatus::Finished { winner } = status {
+            send(clients, &ServerMessage::Over { winner });
+            return;
+        }
+        if clients.iter().all(|c| c.stream.is_none()) {
+            return;
+        }
+    }
+}
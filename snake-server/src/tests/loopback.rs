@@ -0,0 +1,247 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use snake::{Board, BoundaryMode, Direction, Dynamic, GameBuilder, GridBoard, MultiStatus, Pcg32, VecMultiGame};
+
+use crate::*;
+
+/// A client that does what the test tells it to
+struct Scripted {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl Scripted {
+    fn connect(address: SocketAddr) -> Scripted {
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        Scripted { stream, reader }
+    }
+
+    fn join(address: SocketAddr, name: &str) -> Scripted {
+        let mut client = Scripted::connect(address);
+        client.send(&ClientMessage::Join { name: name.into() });
+        client
+    }
+
+    fn send(&mut self, message: &ClientMessage) {
+        writeln!(self.stream, "{}", message).unwrap();
+    }
+
+    fn send_line(&mut self, line: &str) {
+        writeln!(self.stream, "{}", line).unwrap();
+    }
+
+    fn receive(&mut self) -> ServerMessage {
+        let mut line = String::new();
+        assert_ne!(0, self.reader.read_line(&mut line).unwrap(), "the server closed the connection");
+        line.trim().parse().unwrap()
+    }
+
+    /// Everything up to and including the first message `last` accepts
+    fn receive_until(&mut self, last: impl Fn(&ServerMessage) -> bool) -> Vec<ServerMessage> {
+        let mut messages = vec![self.receive()];
+        while !last(messages.last().unwrap()) {
+            messages.push(self.receive());
+        }
+        messages
+    }
+
+    fn is_closed(&mut self) -> bool {
+        let mut line = String::new();
+        matches!(self.reader.read_line(&mut line), Ok(0))
+    }
+}
+
+fn config(boundary_mode: BoundaryMode, interval_ms: u64, rounds: Option<u32>) -> Config {
+    Config {
+        width: 10,
+        height: 6,
+        boundary_mode,
+        seed: 5,
+        interval: Duration::from_millis(interval_ms),
+        restart_after: Duration::from_millis(0),
+        rounds,
+        write_timeout: Duration::from_millis(10),
+    }
+}
+
+fn start(config: Config) -> (SocketAddr, JoinHandle<std::io::Result<()>>) {
+    let server = Server::<2>::bind("127.0.0.1:0", config).unwrap();
+    let address = server.local_addr().unwrap();
+    (address, thread::spawn(move || server.run()))
+}
+
+/// The game the server should be playing
+fn same_game(config: &Config) -> VecMultiGame<Pcg32, 2> {
+    GameBuilder::new(config.width, config.height)
+        .seed(config.seed)
+        .boundary_mode(config.boundary_mode)
+        .build_multi::<Dynamic, Pcg32, 2>()
+}
+
+/// Puts together the board out of the ticks after `START`
+fn board_from(messages: &[ServerMessage], width: usize, height: usize) -> GridBoard<Dynamic> {
+    let mut board = GridBoard::new(width, height);
+    let start = messages.iter().rposition(|m| *m == ServerMessage::Start).unwrap();
+    for message in &messages[start..] {
+        if let ServerMessage::Tick { changes, .. } = message {
+            for (location, square) in changes {
+                *board.at_mut(location) = *square;
+            }
+        }
+    }
+    board
+}
+
+fn is_over(message: &ServerMessage) -> bool {
+    matches!(message, ServerMessage::Over { .. })
+}
+
+#[test]
+fn players_are_welcomed_and_see_the_same_game() {
+    let config = config(BoundaryMode::Solid, 1, Some(1));
+    let (address, server) = start(config.clone());
+
+    let mut ann = Scripted::join(address, "ann");
+    let mut bob = Scripted::join(address, "bob");
+    let ann_saw = ann.receive_until(is_over);
+    let bob_saw = bob.receive_until(is_over);
+    server.join().unwrap().unwrap();
+
+    let welcome = |player| ServerMessage::Welcome {
+        player,
+        players: 2,
+        width: 10,
+        height: 6,
+    };
+    let ann_joined = ServerMessage::Player { player: 0, name: "ann".into() };
+    let bob_joined = ServerMessage::Player { player: 1, name: "bob".into() };
+    assert_eq!([welcome(0), ann_joined.clone(), bob_joined.clone()], ann_saw[..3]);
+    assert_eq!([welcome(1), ann_joined, bob_joined], bob_saw[..3]);
+    assert_eq!(ann_saw[3..], bob_saw[3..]);
+
+    let mut game = same_game(&config);
+    let status = game.advance_until_finished();
+    assert_eq!(Some(&ServerMessage::Over { winner: status }), ann_saw.last());
+    assert!(game.board().iter().eq(board_from(&ann_saw, 10, 6).iter()));
+}
+
+#[test]
+fn turns_are_played_by_the_server() {
+    let config = config(BoundaryMode::Solid, 50, Some(1));
+    let (address, server) = start(config.clone());
+
+    let mut ann = Scripted::join(address, "ann");
+    let mut bob = Scripted::join(address, "bob");
+    bob.send(&ClientMessage::Turn(Direction::Up));
+    let seen = ann.receive_until(is_over);
+    server.join().unwrap().unwrap();
+
+    let mut game = same_game(&config);
+    game.set_direction(1, Direction::Up);
+    let status = game.advance_until_finished();
+    assert_eq!(Some(&ServerMessage::Over { winner: status }), seen.last());
+    assert!(game.board().iter().eq(board_from(&seen, 10, 6).iter()));
+    for player in 0..2 {
+        assert_eq!(!game.is_alive(player), seen.contains(&ServerMessage::Dead { player }));
+    }
+}
+
+#[test]
+fn games_start_again_after_game_over() {
+    let config = config(BoundaryMode::Solid, 1, Some(2));
+    let (address, server) = start(config);
+
+    let mut ann = Scripted::join(address, "ann");
+    let _bob = Scripted::join(address, "bob");
+    let first = ann.receive_until(is_over);
+    let second = ann.receive_until(is_over);
+    server.join().unwrap().unwrap();
+
+    assert!(first.contains(&ServerMessage::Start));
+    assert_eq!(ServerMessage::Start, second[0]);
+    // Both snakes of 2 segments and the apple
+    assert!(matches!(&second[1], ServerMessage::Tick { tick: 0, changes } if changes.len() == 5));
+}
+
+#[test]
+fn only_players_who_join_in_time_get_in() {
+    let (address, server) = start(config(BoundaryMode::Solid, 1, Some(1)));
+
+    let mut rude = Scripted::connect(address);
+    rude.send_line("HELLO");
+    assert_eq!(ServerMessage::Bye { reason: "expected JOIN".into() }, rude.receive());
+    assert!(rude.is_closed());
+
+    let mut ann = Scripted::join(address, "ann");
+    let _bob = Scripted::join(address, "bob");
+    let mut late = Scripted::join(address, "cid");
+    assert_eq!(ServerMessage::Bye { reason: "full".into() }, late.receive());
+
+    ann.receive_until(is_over);
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn silent_connections_dont_hold_up_players() {
+    let (address, server) = start(config(BoundaryMode::Solid, 1, Some(1)));
+
+    let started = std::time::Instant::now();
+    let _silent = Scripted::connect(address);
+    let mut ann = Scripted::join(address, "ann");
+    let _bob = Scripted::join(address, "bob");
+    ann.receive_until(is_over);
+    server.join().unwrap().unwrap();
+
+    // Well before the silent connection is given up on
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn server_stops_once_everyone_left() {
+    // Nothing ever ends the game on a board that wraps with snakes going straight
+    let (address, server) = start(config(BoundaryMode::Wrap, 1, None));
+
+    let mut ann = Scripted::join(address, "ann");
+    let bob = Scripted::join(address, "bob");
+    ann.receive_until(|m| matches!(m, ServerMessage::Tick { tick: 3, .. }));
+    ann.send(&ClientMessage::Quit);
+    ann.receive_until(|m| matches!(m, ServerMessage::Bye { .. }));
+    bob.stream.shutdown(std::net::Shutdown::Both).unwrap();
+
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn players_who_stop_reading_are_dropped() {
+    // Ticks come as fast as they can until the connections are full
+    let (address, server) = start(config(BoundaryMode::Wrap, 0, None));
+
+    let _ann = Scripted::join(address, "ann");
+    let mut bob = Scripted::join(address, "bob");
+    server.join().unwrap().unwrap();
+
+    // What was sent before is still there, then the connection is closed
+    let mut line = String::new();
+    while bob.reader.read_line(&mut line).unwrap() > 0 {
+        line.clear();
+    }
+}
+
+trait UntilFinished {
+    fn advance_until_finished(&mut self) -> Option<usize>;
+}
+
+impl UntilFinished for VecMultiGame<Pcg32, 2> {
+    fn advance_until_finished(&mut self) -> Option<usize> {
+        loop {
+            if let MultiStatus::Finished { winner } = self.advance() {
+                return winner;
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use snake::{Direction, FruitKind, Location, Square};
+
+use crate::*;
+
+#[test]
+fn messages_are_written_as_they_are_read() {
+    let client = [
+        ClientMessage::Join { name: "ann".into() },
+        ClientMessage::Turn(Direction::Left),
+        ClientMessage::Quit,
+    ];
+    for message in client {
+        assert_eq!(Ok(message.clone()), message.to_string().parse());
+    }
+
+    let server = [
+        ServerMessage::Welcome {
+            player: 1,
+            players: 3,
+            width: 40,
+            height: 20,
+        },
+        ServerMessage::Player { player: 2, name: "bob".into() },
+        ServerMessage::Start,
+        ServerMessage::Tick {
+            tick: 7,
+            changes: vec![
+                (Location::new(3, 4), Square::Player(11)),
+                (Location::new(0, 19), Square::Fruit(FruitKind::Bonus)),
+                (Location::new(5, 5), Square::Empty),
+            ],
+        },
+        ServerMessage::Tick { tick: 8, changes: vec![] },
+        ServerMessage::Score { player: 0, score: 12 },
+        ServerMessage::Dead { player: 2 },
+        ServerMessage::Over { winner: Some(1) },
+        ServerMessage::Over { winner: None },
+        ServerMessage::Bye { reason: "expected JOIN".into() },
+    ];
+    for message in server {
+        assert_eq!(Ok(message.clone()), message.to_string().parse());
+    }
+}
+
+#[test]
+fn ticks_list_squares_as_in_levels() {
+    let tick = ServerMessage::Tick {
+        tick: 1,
+        changes: vec![(Location::new(8, 6), Square::Empty), (Location::new(10, 7), Square::Player(0))],
+    };
+
+    assert_eq!("TICK 1 8,6,. 10,7,0", tick.to_string());
+}
+
+#[test]
+fn malformed_lines_are_rejected() {
+    for line in ["", "JOIN", "JOIN ann bob", "TURN north", "turn up", "QUIT now"] {
+        assert_eq!(Err(ProtocolError(line.into())), line.parse::<ClientMessage>());
+    }
+    for line in ["WELCOME 0 2 40", "TICK", "TICK 1 2,3", "TICK 1 2,3,Z", "TICK 1 a,3,F", "OVER", "SCORE 1"] {
+        assert_eq!(Err(ProtocolError(line.into())), line.parse::<ServerMessage>());
+    }
+}
@@ -0,0 +1,53 @@
+#!/usr/bin/env python3
+"""A bot for the snake-sim tournament, heads for the apple without running into anything.
+
+Reads the messages described in snake-sim/src/bot.rs from stdin and answers on stdout.
+"""
+
+import sys
+
+MOVES = {"up": (0, -1), "down": (0, 1), "left": (-1, 0), "right": (1, 0)}
+OPPOSITE = {"up": "down", "down": "up", "left": "right", "right": "left"}
+
+
+def main():
+    width = height = 0
+    wraps = True
+    lines = iter(sys.stdin.readline, "")
+    for line in lines:
+        words = line.split()
+        if not words:
+            continue
+        if words[0] == "GAME":
+            width, height, wraps = int(words[1]), int(words[2]), words[3] == "wrap"
+        elif words[0] == "TICK":
+            heading = words[3]
+            rows = [next(lines).rstrip("\n") for _ in range(height)]
+            print(choose(rows, width, height, wraps, heading), flush=True)
+        elif words[0] == "OVER":
+            return
+
+
+def choose(rows, width, height, wraps, heading):
+    find = lambda c: next(((x, y) for y, row in enumerate(rows) for x, s in enumerate(row) if s == c), None)
+    head, apple = find("H"), find("F")
+
+    def target(move):
+        dx, dy = MOVES[move]
+        x, y = head[0] + dx, head[1] + dy
+        if wraps:
+            x, y = x % width, y % height
+        elif not (0 <= x < width and 0 <= y < height):
+            return None
+        return (x, y) if rows[y][x] in ".F$%-" else None
+
+    safe = [move for move in MOVES if move != OPPOSITE[heading] and target(move)]
+    if not safe:
+        return heading
+    if apple is None:
+        return safe[0]
+    return min(safe, key=lambda move: abs(target(move)[0] - apple[0]) + abs(target(move)[1] - apple[1]))
+
+
+if __name__ == "__main__":
+    main()
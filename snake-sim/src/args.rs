@@ -0,0 +1,86 @@
+//! The command line, options are looked up by name and their values checked when asked for.
+
+use std::str::FromStr;
+
+use snake::{BoundaryMode, FruitPolicy};
+
+use crate::stats::Format;
+
+pub struct Args(Vec<String>);
+
+impl Args {
+    pub fn new(args: impl IntoIterator<Item = String>) -> Args {
+        Args(args.into_iter().collect())
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.0.iter().skip_while(|a| *a != name).nth(1).map(String::as_str)
+    }
+
+    /// Values of an option given more than once
+    pub fn values(&self, name: &str) -> Vec<&str> {
+        self.0
+            .windows(2)
+            .filter(|pair| pair[0] == name)
+            .map(|pair| pair[1].as_str())
+            .collect()
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.0.iter().any(|a| a == name)
+    }
+
+    pub fn number<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.value(name) {
+            None => Ok(default),
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("Invalid {} '{}', expected a number", name, value)),
+        }
+    }
+
+    pub fn boundary_mode(&self) -> Result<BoundaryMode, String> {
+        match self.value("--boundary") {
+            None | Some("wrap") => Ok(BoundaryMode::Wrap),
+            Some("solid") => Ok(BoundaryMode::Solid),
+            Some("horizontal") => Ok(BoundaryMode::WrapHorizontally),
+            Some("vertical") => Ok(BoundaryMode::WrapVertically),
+            Some(other) => Err(format!(
+                "Unknown boundary mode '{}', expected wrap, solid, horizontal or vertical",
+                other
+            )),
+        }
+    }
+
+    pub fn fruit_policy(&self) -> Result<FruitPolicy, String> {
+        match self.value("--fruit") {
+            None | Some("apples") => Ok(FruitPolicy::new()),
+            Some("mixed") => Ok(FruitPolicy::mixed()),
+            Some(other) => Err(format!("Unknown fruit '{}', expected apples or mixed", other)),
+        }
+    }
+
+    pub fn format(&self) -> Result<Format, String> {
+        match self.value("--format") {
+            None | Some("table") => Ok(Format::Table),
+            Some("csv") => Ok(Format::Csv),
+            Some("json") => Ok(Format::Json),
+            Some(other) => Err(format!("Unknown format '{}', expected table, csv or json", other)),
+        }
+    }
+
+    /// Board size, 20x20 unless given
+    pub fn size(&self) -> Result<(usize, usize), String> {
+        let Some(size) = self.value("--size") else {
+            return Ok((20, 20));
+        };
+        let parsed = size
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+
+        match parsed {
+            Some((width, height)) if width >= 3 && height >= 1 => Ok((width, height)),
+            _ => Err(format!("Invalid board size '{}', expected WIDTHxHEIGHT of at least 3x1", size)),
+        }
+    }
+}
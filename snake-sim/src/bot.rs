@@ -0,0 +1,296 @@
+//! Who plays the games, either a controller written in Rust or a program of its own.
+//!
+//! Rust bots implement `snake::Controller` and are listed in `Bot::parse`. Any other bot is a
+//! command, split at whitespace, that's started for every game and talks to the runner over
+//! its stdin and stdout, one message per line:
+//!
+//! * `GAME width height boundary` starts the game, the boundary is `wrap`, `solid`,
+//!   `horizontal` or `vertical`
+//! * `TICK tick score direction` followed by the board, `height` lines of `width` squares,
+//!   comes before every tick. Squares are drawn as in levels except that empty ones are `.`
+//!   and the head is `H`; bonus fruits are `$`, feasts `%` and shrinking fruits `-`
+//! * the bot answers every `TICK` with `up`, `down`, `left` or `right`, anything else keeps
+//!   the snake going the way it heads
+//! * `OVER outcome score length` ends the game, the outcome is `won`, `lost` or `timeout`,
+//!   then stdin is closed
+//!
+//! A bot that doesn't answer within the move time, a second longer for the first tick, keeps
+//! going the way it heads and the move counts as slow, so does a tick the bot is still too
+//! busy to be told about. Bots that keep being slow or exit are not waited for anymore.
+
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::time::Duration;
+
+use snake::{
+    BoundaryMode, Controller, Direction, Dynamic, FruitKind, Greedy, Hamiltonian, Location, Pcg32,
+    RandomNumberGenerator, Snake, Square,
+};
+
+use crate::stats::{GameResult, Outcome};
+
+/// Slow moves in a row after which a bot is taken for stuck
+const GIVE_UP_AFTER: u32 = 10;
+
+/// Messages waiting to be written to a bot, beyond them it isn't reading its input
+const QUEUED_MESSAGES: usize = 4;
+
+/// How long a bot has to exit after its game ended
+const EXIT_TIME: Duration = Duration::from_secs(1);
+
+/// Time a bot has on top of the move time to answer its first tick, while it starts up
+const START_TIME: Duration = Duration::from_secs(1);
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Bot {
+    Greedy,
+    Hamiltonian,
+    Random,
+    /// A program and its arguments
+    Process(Vec<String>),
+}
+
+impl Bot {
+    /// Names of the Rust bots are taken for them, everything else is a command
+    pub fn parse(spec: &str) -> Option<Bot> {
+        match spec {
+            "greedy" => Some(Bot::Greedy),
+            "hamiltonian" => Some(Bot::Hamiltonian),
+            "random" => Some(Bot::Random),
+            command => {
+                let words: Vec<String> = command.split_whitespace().map(String::from).collect();
+                (!words.is_empty()).then_some(Bot::Process(words))
+            }
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Bot::Greedy => "greedy".into(),
+            Bot::Hamiltonian => "hamiltonian".into(),
+            Bot::Random => "random".into(),
+            Bot::Process(command) => command.join(" "),
+        }
+    }
+
+    /// A Rust bot for a game started with `seed`, `None` for processes
+    pub fn controller(&self, width: usize, height: usize, seed: u64) -> Result<Option<Box<dyn Controller>>, String> {
+        Ok(Some(match self {
+            Bot::Greedy => Box::new(Greedy::<Dynamic>::new(width, height)),
+            Bot::Hamiltonian => match Hamiltonian::<Dynamic>::new(width, height) {
+                Some(hamiltonian) => Box::new(hamiltonian),
+                None => return Err("the hamiltonian bot needs a board with an even width or height".into()),
+            },
+            Bot::Random => Box::new(Random(Pcg32::from_seed(seed))),
+            Bot::Process(_) => return Ok(None),
+        }))
+    }
+}
+
+/// Turns at random now and then, to compare the others against
+struct Random(Pcg32);
+
+impl Controller for Random {
+    fn next_direction(&mut self, game: &dyn Snake) -> Option<Direction> {
+        const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+        let roll = self.0.next();
+        let direction = DIRECTIONS[(roll % 4) as usize];
+        (roll % 16 < 4 && direction != game.direction()).then_some(direction)
+    }
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "up",
+        Direction::Down => "down",
+        Direction::Left => "left",
+        Direction::Right => "right",
+    }
+}
+
+fn boundary_name(boundary_mode: BoundaryMode) -> &'static str {
+    match boundary_mode {
+        BoundaryMode::Wrap => "wrap",
+        BoundaryMode::Solid => "solid",
+        BoundaryMode::WrapHorizontally => "horizontal",
+        BoundaryMode::WrapVertically => "vertical",
+    }
+}
+
+fn square_char(square: Square) -> char {
+    match square {
+        Square::Empty => '.',
+        Square::Wall => '#',
+        Square::Snake | Square::Player(_) => 'O',
+        Square::Fruit(FruitKind::Apple) => 'F',
+        Square::Fruit(FruitKind::Bonus) => '$',
+        Square::Fruit(FruitKind::Feast) => '%',
+        Square::Fruit(FruitKind::Shrink) => '-',
+    }
+}
+
+/// A bot running as a process of its own for one game
+pub struct Process {
+    child: Child,
+    /// Messages for the thread writing them to the bot, so that a bot that doesn't read can't
+    /// block the game, `None` once the bot is gone
+    input: Option<SyncSender<String>>,
+    answers: Receiver<String>,
+    /// Answers to ticks the bot was too slow for are skipped
+    stale: usize,
+    slow_in_a_row: u32,
+    started: bool,
+    pub slow_moves: u32,
+}
+
+impl Process {
+    pub fn start(command: &[String], game: &dyn Snake) -> std::io::Result<Process> {
+        let mut child = Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().map(BufWriter::new);
+        let output = child.stdout.take().map(BufReader::new);
+
+        let (input, messages) = mpsc::sync_channel::<String>(QUEUED_MESSAGES);
+        if let Some(mut stdin) = stdin {
+            std::thread::spawn(move || {
+                for message in messages {
+                    if stdin.write_all(message.as_bytes()).and_then(|_| stdin.flush()).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        let (answer, answers) = mpsc::channel();
+        if let Some(output) = output {
+            std::thread::spawn(move || {
+                for line in output.lines() {
+                    let Ok(line) = line else { break };
+                    if answer.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        let mut process = Process {
+            child,
+            input: Some(input),
+            answers,
+            stale: 0,
+            slow_in_a_row: 0,
+            started: false,
+            slow_moves: 0,
+        };
+        let board = game.board();
+        let boundary = boundary_name(game.boundary_mode());
+        process.send(format!("GAME {} {} {}\n", board.width(), board.height(), boundary));
+        Ok(process)
+    }
+
+    /// Queues `message` for the bot, false when the bot isn't keeping up with its input
+    fn send(&mut self, message: String) -> bool {
+        match self.input.as_ref().map(|input| input.try_send(message)) {
+            Some(Ok(())) => true,
+            Some(Err(TrySendError::Full(_))) => false,
+            Some(Err(TrySendError::Disconnected(_))) | None => {
+                self.input = None;
+                false
+            }
+        }
+    }
+
+    /// Whether the bot exited or was given up on
+    pub fn is_gone(&self) -> bool {
+        self.input.is_none()
+    }
+
+    /// Tells the bot how the game ended and stops it
+    pub fn finish(mut self, result: &GameResult) {
+        let outcome = match result.outcome {
+            Outcome::Won => "won",
+            Outcome::Lost => "lost",
+            Outcome::TimedOut => "timeout",
+        };
+        self.send(format!("OVER {} {} {}\n", outcome, result.score, result.length));
+        self.stop();
+    }
+
+    /// Closes stdin and waits a bit for the bot to exit before killing it
+    pub fn stop(mut self) {
+        self.input = None;
+
+        let deadline = std::time::Instant::now() + EXIT_TIME;
+        while matches!(self.child.try_wait(), Ok(None)) && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+
+    /// Asks the bot where to go, waiting at most `move_time` for the answer
+    pub fn ask(&mut self, game: &dyn Snake, move_time: Duration) -> Option<Direction> {
+        if self.slow_in_a_row >= GIVE_UP_AFTER {
+            self.input = None;
+        }
+        // Nobody to ask once the bot is gone
+        self.input.as_ref()?;
+
+        let board = game.board();
+        let head = game.head();
+        let mut message = String::with_capacity((board.width() + 1) * (board.height() + 1));
+        let direction = direction_name(game.direction());
+        writeln!(message, "TICK {} {} {}", game.ticks(), game.score(), direction).unwrap();
+        for y in 0..board.height() {
+            message.extend((0..board.width()).map(|x| match Location::new(x, y) {
+                location if location == head => 'H',
+                location => square_char(board.at(location)),
+            }));
+            message.push('\n');
+        }
+        if !self.send(message) {
+            return if self.is_gone() { None } else { self.slow() };
+        }
+
+        let start_time = if self.started { Duration::ZERO } else { START_TIME };
+        self.started = true;
+        let deadline = std::time::Instant::now() + start_time + move_time;
+        let answer = loop {
+            let left = deadline.saturating_duration_since(std::time::Instant::now());
+            match self.answers.recv_timeout(left) {
+                Ok(_) if self.stale > 0 => self.stale -= 1,
+                Ok(answer) => break Some(answer),
+                Err(RecvTimeoutError::Timeout) => break None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.input = None;
+                    return None;
+                }
+            }
+        };
+
+        let Some(answer) = answer else {
+            // The answer may still come, it's not for the next tick then
+            self.stale += 1;
+            return self.slow();
+        };
+        self.slow_in_a_row = 0;
+        match answer.trim() {
+            "up" => Some(Direction::Up),
+            "down" => Some(Direction::Down),
+            "left" => Some(Direction::Left),
+            "right" => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    fn slow(&mut self) -> Option<Direction> {
+        self.slow_in_a_row += 1;
+        self.slow_moves += 1;
+        None
+    }
+}
@@ -0,0 +1,237 @@
+//! Plays many seeded games without a UI and reports how a controller did.
+//!
+//! Games are driven only through `Snake::advance` and `Snake::set_direction`, the same way the
+//! frontends drive them, game `i` is started with seed `--seed` + `i`. In a tournament every
+//! bot plays the same games and they're ranked by the games they won, then by the length of
+//! their snakes and then by how few games timed out.
+
+use std::time::Duration;
+
+use snake::{Dynamic, GameBuilder, GameStatus, Level, Pcg32, Snake, VecGame};
+
+mod args;
+mod bot;
+mod stats;
+
+use args::Args;
+use bot::{Bot, Process};
+use stats::{GameResult, Outcome, Standing, Summary};
+
+const USAGE: &str = "\
+Usage: snake-sim [options]
+  --controller BOT                        who plays, greedy by default
+  --bot BOT                               plays a tournament of every bot given, the option
+                                          can be repeated
+  --move-time MS                          time bots running as processes have to answer,
+                                          100 by default
+  --games N                               number of games, 1000 by default
+  --seed N                                seed of the first game, 0 by default
+  --size WIDTHxHEIGHT                     board size, 20x20 by default
+  --level PATH                            play on a level instead
+  --boundary wrap|solid|horizontal|vertical
+  --fruit apples|mixed
+  --max-idle N                            games time out after N ticks without an apple,
+                                          4 ticks per square by default
+  --format table|csv|json                 table by default
+  --per-game                              print every game instead of the summary
+
+Bots are greedy, hamiltonian, random or a command, which plays over stdin and stdout as
+described in snake-sim/src/bot.rs, e.g. --bot \"python3 bots/example.py\"";
+
+fn fail(message: std::fmt::Arguments) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+/// Exits with the error
+fn or_fail<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|e| fail(format_args!("{}", e)))
+}
+
+fn bot_from(spec: &str) -> Bot {
+    Bot::parse(spec).unwrap_or_else(|| fail(format_args!("Invalid bot '{}', expected a name or a command", spec)))
+}
+
+/// Everything the games share, each one only gets its own seed
+struct Setup {
+    builder: GameBuilder<'static>,
+    width: usize,
+    height: usize,
+    /// Ticks without eating an apple after which a game times out, a snake going round in
+    /// circles would never end its game
+    max_idle: u32,
+    move_time: Duration,
+}
+
+impl Setup {
+    fn from_args(args: &Args) -> Result<Setup, String> {
+        let (builder, width, height) = match args.value("--level") {
+            Some(path) => {
+                let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+                // Every game is built from the same level until the program ends
+                let text: &'static str = Box::leak(text.into_boxed_str());
+                let level = Level::parse(text).map_err(|e| format!("{}:{}", path, e))?;
+                (GameBuilder::new(0, 0).level(&level), level.width(), level.height())
+            }
+            None => {
+                let (width, height) = args.size()?;
+                (GameBuilder::new(width, height), width, height)
+            }
+        };
+
+        let setup = Setup {
+            builder: builder.boundary_mode(args.boundary_mode()?).fruit_policy(args.fruit_policy()?),
+            width,
+            height,
+            max_idle: args.number("--max-idle", (4 * width * height).try_into().unwrap_or(u32::MAX))?,
+            move_time: Duration::from_millis(args.number("--move-time", 100)?),
+        };
+        // Fail before any game is played
+        setup
+            .builder
+            .clone()
+            .seed(0)
+            .try_build::<Dynamic, Pcg32>()
+            .map_err(|e| format!("Can't start the game: {}", e))?;
+        Ok(setup)
+    }
+
+    /// Fails unless `bot` can play, bots running as processes are started once to see that
+    fn check(&self, bot: &Bot) {
+        if let Bot::Process(command) = bot {
+            let game: VecGame<Pcg32> = self.builder.clone().seed(0).build();
+            Process::start(command, &game)
+                .unwrap_or_else(|e| fail(format_args!("{}: {}", bot.name(), e)))
+                .stop();
+        } else if let Err(e) = bot.controller(self.width, self.height, 0) {
+            fail(format_args!("{}: {}", bot.name(), e));
+        }
+    }
+
+    fn play(&self, seed: u64, bot: &Bot) -> GameResult {
+        let mut game: VecGame<Pcg32> = self.builder.clone().seed(seed).build();
+        let game: &mut dyn Snake = &mut game;
+        let fail_to_start = |e: &dyn std::fmt::Display| -> ! { fail(format_args!("{}: {}", bot.name(), e)) };
+        let mut controller = bot.controller(self.width, self.height, seed).unwrap_or_else(|e| fail_to_start(&e));
+        let mut process = match bot {
+            Bot::Process(command) => Some(Process::start(command, game).unwrap_or_else(|e| fail_to_start(&e))),
+            _ => None,
+        };
+        let (mut apples, mut idle) = (0, 0);
+
+        let outcome = loop {
+            if idle >= self.max_idle {
+                break Outcome::TimedOut;
+            }
+            let direction = match (&mut controller, &mut process) {
+                (Some(controller), _) => controller.next_direction(game),
+                (None, Some(process)) => process.ask(game, self.move_time),
+                (None, None) => None,
+            };
+            if let Some(direction) = direction {
+                game.set_direction(direction);
+            }
+            let apple = game.fruit();
+            let status = game.advance();
+            // The head only ends up where the apple was by eating it, an apple that comes back
+            // once extra fruits stop covering the free squares isn't one eaten
+            match apple.is_some_and(|apple| game.head() == apple) {
+                true => (apples, idle) = (apples + 1, 0),
+                false => idle += 1,
+            }
+            match status {
+                GameStatus::InProgress | GameStatus::Paused => {}
+                GameStatus::Won => break Outcome::Won,
+                GameStatus::Lost => break Outcome::Lost,
+            }
+        };
+
+        let mut result = GameResult {
+            seed,
+            outcome,
+            length: game.length(),
+            ticks: game.ticks(),
+            score: game.score(),
+            apples,
+            slow_moves: 0,
+        };
+        if let Some(process) = process {
+            result.slow_moves = process.slow_moves;
+            process.finish(&result);
+        }
+        result
+    }
+
+    fn title(&self, who: &str, games: usize, first_seed: u64) -> String {
+        format!(
+            "{} on {}x{}, {} games from seed {}",
+            who, self.width, self.height, games, first_seed
+        )
+    }
+}
+
+/// Plays the games on all cores, the results are in the order of the seeds
+fn play_all(setup: &Setup, bot: &Bot, first_seed: u64, games: usize) -> Vec<GameResult> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let seeds: Vec<u64> = (0..games as u64).map(|i| first_seed.wrapping_add(i)).collect();
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = seeds
+            .chunks(games.div_ceil(threads).max(1))
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|seed| setup.play(*seed, bot)).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+fn main() {
+    let args = Args::new(std::env::args());
+    if args.has("--help") || args.has("-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let setup = or_fail(Setup::from_args(&args));
+    let games = or_fail(args.number("--games", 1000));
+    let first_seed = or_fail(args.number("--seed", 0));
+    let format = or_fail(args.format());
+
+    let bots = args.values("--bot");
+    if !bots.is_empty() {
+        let bots: Vec<Bot> = bots.into_iter().map(bot_from).collect();
+        bots.iter().for_each(|bot| setup.check(bot));
+        let standings = bots
+            .iter()
+            .map(|bot| Standing {
+                bot: bot.name(),
+                summary: Summary::of(&play_all(&setup, bot, first_seed, games)),
+            })
+            .collect();
+        let title = setup.title("Tournament", games, first_seed);
+        print!("{}", stats::leaderboard(format, &title, stats::rank(standings)));
+        return;
+    }
+
+    let bot = bot_from(args.value("--controller").unwrap_or("greedy"));
+    setup.check(&bot);
+    let results = play_all(&setup, &bot, first_seed, games);
+
+    if args.has("--per-game") {
+        print!("{}", stats::games(format, &results));
+    } else {
+        print!(
+            "{}",
+            stats::summary(format, &setup.title(&bot.name(), games, first_seed), &Summary::of(&results))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod args;
+    mod bot;
+    mod stats;
+}
@@ -0,0 +1,335 @@
+//! Results of single games and what they add up to.
+
+use std::fmt::Write;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Outcome {
+    Won,
+    Lost,
+    /// Went on for too long without eating
+    TimedOut,
+}
+
+impl Outcome {
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Won => "won",
+            Outcome::Lost => "lost",
+            Outcome::TimedOut => "timed out",
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct GameResult {
+    pub seed: u64,
+    pub outcome: Outcome,
+    pub length: usize,
+    pub ticks: u32,
+    pub score: u32,
+    /// Apples eaten, extra fruits are not counted
+    pub apples: u32,
+    /// Ticks a bot running as a process didn't answer in time
+    pub slow_moves: u32,
+}
+
+impl GameResult {
+    pub fn apples_per_tick(&self) -> f64 {
+        ratio(self.apples.into(), self.ticks.into())
+    }
+}
+
+fn ratio(count: f64, ticks: f64) -> f64 {
+    if ticks == 0.0 {
+        0.0
+    } else {
+        count / ticks
+    }
+}
+
+/// Mean, minimum, median and maximum of one measurement over all games
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct Spread {
+    pub mean: f64,
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+}
+
+impl Spread {
+    fn of(mut values: Vec<f64>) -> Spread {
+        if values.is_empty() {
+            return Spread::default();
+        }
+        values.sort_by(f64::total_cmp);
+        let middle = values.len() / 2;
+        Spread {
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            min: values[0],
+            median: match values.len() % 2 {
+                0 => (values[middle - 1] + values[middle]) / 2.0,
+                _ => values[middle],
+            },
+            max: values[values.len() - 1],
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Summary {
+    pub games: usize,
+    pub won: usize,
+    pub lost: usize,
+    pub timed_out: usize,
+    pub length: Spread,
+    pub ticks: Spread,
+    pub score: Spread,
+    /// Apples eaten in all games over the ticks played in all games
+    pub apples_per_tick: f64,
+    pub slow_moves: u32,
+}
+
+impl Summary {
+    pub fn of(results: &[GameResult]) -> Summary {
+        let count = |outcome| results.iter().filter(|r| r.outcome == outcome).count();
+        let spread = |value: fn(&GameResult) -> f64| Spread::of(results.iter().map(value).collect());
+        Summary {
+            games: results.len(),
+            won: count(Outcome::Won),
+            lost: count(Outcome::Lost),
+            timed_out: count(Outcome::TimedOut),
+            length: spread(|r| r.length as f64),
+            ticks: spread(|r| r.ticks.into()),
+            score: spread(|r| r.score.into()),
+            apples_per_tick: ratio(
+                results.iter().map(|r| f64::from(r.apples)).sum(),
+                results.iter().map(|r| f64::from(r.ticks)).sum(),
+            ),
+            slow_moves: results.iter().map(|r| r.slow_moves).sum(),
+        }
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        ratio(self.won as f64, self.games as f64)
+    }
+}
+
+/// How a bot did in a tournament
+#[derive(PartialEq, Clone, Debug)]
+pub struct Standing {
+    pub bot: String,
+    pub summary: Summary,
+}
+
+/// Best first, by the games won, then the mean length and then the fewest games timed out
+pub fn rank(mut standings: Vec<Standing>) -> Vec<Standing> {
+    standings.sort_by(|a, b| {
+        let (a, b) = (&a.summary, &b.summary);
+        b.win_rate()
+            .total_cmp(&a.win_rate())
+            .then(b.length.mean.total_cmp(&a.length.mean))
+            .then(a.timed_out.cmp(&b.timed_out))
+    });
+    standings
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+const SPREADS: [&str; 3] = ["length", "ticks", "score"];
+
+fn spreads(summary: &Summary) -> [Spread; 3] {
+    [summary.length, summary.ticks, summary.score]
+}
+
+/// Prints what all games add up to, `title` tells what was played
+pub fn summary(format: Format, title: &str, summary: &Summary) -> String {
+    let mut out = String::new();
+    match format {
+        Format::Table => {
+            writeln!(out, "{}", title).unwrap();
+            writeln!(
+                out,
+                "{} games: {} won, {} lost, {} timed out",
+                summary.games, summary.won, summary.lost, summary.timed_out
+            )
+            .unwrap();
+            writeln!(out, "{:<8}{:>10}{:>10}{:>10}{:>10}", "", "mean", "min", "median", "max").unwrap();
+            for (name, s) in SPREADS.iter().zip(spreads(summary)) {
+                writeln!(out, "{:<8}{:>10.1}{:>10}{:>10}{:>10}", name, s.mean, s.min, s.median, s.max).unwrap();
+            }
+            writeln!(out, "apples per tick {:.4}", summary.apples_per_tick).unwrap();
+        }
+        Format::Csv => {
+            let mut header = String::from("games,won,lost,timed_out");
+            let mut row = format!("{},{},{},{}", summary.games, summary.won, summary.lost, summary.timed_out);
+            for (name, s) in SPREADS.iter().zip(spreads(summary)) {
+                for (field, value) in [("mean", s.mean), ("min", s.min), ("median", s.median), ("max", s.max)] {
+                    write!(header, ",{}_{}", name, field).unwrap();
+                    write!(row, ",{}", value).unwrap();
+                }
+            }
+            writeln!(out, "{},apples_per_tick\n{},{}", header, row, summary.apples_per_tick).unwrap();
+        }
+        Format::Json => {
+            write!(
+                out,
+                "{{\"title\":{:?},\"games\":{},\"won\":{},\"lost\":{},\"timed_out\":{}",
+                title, summary.games, summary.won, summary.lost, summary.timed_out
+            )
+            .unwrap();
+            for (name, s) in SPREADS.iter().zip(spreads(summary)) {
+                write!(
+                    out,
+                    ",\"{}\":{{\"mean\":{},\"min\":{},\"median\":{},\"max\":{}}}",
+                    name, s.mean, s.min, s.median, s.max
+                )
+                .unwrap();
+            }
+            writeln!(out, ",\"apples_per_tick\":{}}}", summary.apples_per_tick).unwrap();
+        }
+    }
+    out
+}
+
+/// Prints every game on its own
+pub fn games(format: Format, results: &[GameResult]) -> String {
+    let mut out = String::new();
+    match format {
+        Format::Table => {
+            writeln!(
+                out,
+                "{:>20}{:>10}{:>8}{:>8}{:>8}{:>8}{:>16}",
+                "seed", "outcome", "length", "ticks", "score", "apples", "apples per tick"
+            )
+            .unwrap();
+            for r in results {
+                writeln!(
+                    out,
+                    "{:>20}{:>10}{:>8}{:>8}{:>8}{:>8}{:>16.4}",
+                    r.seed,
+                    r.outcome.name(),
+                    r.length,
+                    r.ticks,
+                    r.score,
+                    r.apples,
+                    r.apples_per_tick()
+                )
+                .unwrap();
+            }
+        }
+        Format::Csv => {
+            writeln!(out, "seed,outcome,length,ticks,score,apples,apples_per_tick").unwrap();
+            for r in results {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    r.seed,
+                    r.outcome.name(),
+                    r.length,
+                    r.ticks,
+                    r.score,
+                    r.apples,
+                    r.apples_per_tick()
+                )
+                .unwrap();
+            }
+        }
+        Format::Json => {
+            let objects: Vec<String> = results
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{{\"seed\":{},\"outcome\":\"{}\",\"length\":{},\"ticks\":{},\"score\":{},\"apples\":{},\"apples_per_tick\":{}}}",
+                        r.seed,
+                        r.outcome.name(),
+                        r.length,
+                        r.ticks,
+                        r.score,
+                        r.apples,
+                        r.apples_per_tick()
+                    )
+                })
+                .collect();
+            writeln!(out, "[{}]", objects.join(",")).unwrap();
+        }
+    }
+    out
+}
+
+/// Prints the standings in the order they're in, `title` tells what was played
+pub fn leaderboard(format: Format, title: &str, standings: Vec<Standing>) -> String {
+    let mut out = String::new();
+    match format {
+        Format::Table => {
+            let width = standings.iter().map(|s| s.bot.len()).max().unwrap_or(0).max(3);
+            writeln!(out, "{}", title).unwrap();
+            writeln!(
+                out,
+                "{:>4}  {:<width$}{:>8}{:>10}{:>10}{:>10}{:>12}{:>12}",
+                "rank", "bot", "won", "win rate", "length", "score", "timed out", "slow moves"
+            )
+            .unwrap();
+            for (rank, Standing { bot, summary: s }) in standings.iter().enumerate() {
+                writeln!(
+                    out,
+                    "{:>4}  {:<width$}{:>8}{:>9.1}%{:>10.1}{:>10.1}{:>12}{:>12}",
+                    rank + 1,
+                    bot,
+                    s.won,
+                    100.0 * s.win_rate(),
+                    s.length.mean,
+                    s.score.mean,
+                    s.timed_out,
+                    s.slow_moves
+                )
+                .unwrap();
+            }
+        }
+        Format::Csv => {
+            writeln!(out, "rank,bot,games,won,win_rate,length_mean,score_mean,timed_out,slow_moves").unwrap();
+            for (rank, Standing { bot, summary: s }) in standings.iter().enumerate() {
+                writeln!(
+                    out,
+                    "{},\"{}\",{},{},{},{},{},{},{}",
+                    rank + 1,
+                    bot.replace('"', "\"\""),
+                    s.games,
+                    s.won,
+                    s.win_rate(),
+                    s.length.mean,
+                    s.score.mean,
+                    s.timed_out,
+                    s.slow_moves
+                )
+                .unwrap();
+            }
+        }
+        Format::Json => {
+            let objects: Vec<String> = standings
+                .iter()
+                .enumerate()
+                .map(|(rank, Standing { bot, summary: s })| {
+                    format!(
+                        "{{\"rank\":{},\"bot\":{:?},\"games\":{},\"won\":{},\"win_rate\":{},\"length_mean\":{},\"score_mean\":{},\"timed_out\":{},\"slow_moves\":{}}}",
+                        rank + 1,
+                        bot,
+                        s.games,
+                        s.won,
+                        s.win_rate(),
+                        s.length.mean,
+                        s.score.mean,
+                        s.timed_out,
+                        s.slow_moves
+                    )
+                })
+                .collect();
+            writeln!(out, "{{\"title\":{:?},\"standings\":[{}]}}", title, objects.join(",")).unwrap();
+        }
+    }
+    out
+}
@@ -0,0 +1,54 @@
+use snake::BoundaryMode;
+
+use crate::args::Args;
+use crate::stats::Format;
+
+fn args(line: &str) -> Args {
+    Args::new(line.split_whitespace().map(String::from))
+}
+
+#[test]
+fn options_have_defaults() {
+    let args = args("snake-sim");
+
+    assert_eq!(Ok((20, 20)), args.size());
+    assert_eq!(Ok(BoundaryMode::Wrap), args.boundary_mode());
+    assert_eq!(Ok(Format::Table), args.format());
+    assert_eq!(Ok(1000), args.number("--games", 1000));
+    assert_eq!(None, args.value("--controller"));
+    assert!(!args.has("--per-game"));
+}
+
+#[test]
+fn options_are_read_by_name() {
+    let args = args("snake-sim --games 50 --size 10x8 --boundary vertical --format json --per-game --seed 7");
+
+    assert_eq!(Ok((10, 8)), args.size());
+    assert_eq!(Ok(BoundaryMode::WrapVertically), args.boundary_mode());
+    assert_eq!(Ok(Format::Json), args.format());
+    assert_eq!(Ok(50), args.number("--games", 1000));
+    assert_eq!(Ok(7u64), args.number("--seed", 0));
+    assert!(args.has("--per-game"));
+}
+
+#[test]
+fn repeated_options_give_every_value() {
+    let args = args("snake-sim --bot greedy --games 3 --bot random");
+
+    assert_eq!(vec!["greedy", "random"], args.values("--bot"));
+    assert_eq!(Some("greedy"), args.value("--bot"));
+}
+
+#[test]
+fn invalid_values_are_errors() {
+    assert_eq!(
+        Err("Invalid --games 'many', expected a number".to_string()),
+        args("snake-sim --games many").number("--games", 1000)
+    );
+    for size in ["2x5", "10", "10x0", "axb"] {
+        assert!(args(&format!("snake-sim --size {}", size)).size().is_err(), "{}", size);
+    }
+    assert!(args("snake-sim --boundary sticky").boundary_mode().is_err());
+    assert!(args("snake-sim --fruit pears").fruit_policy().is_err());
+    assert!(args("snake-sim --format xml").format().is_err());
+}
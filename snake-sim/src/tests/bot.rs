@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+
+use snake::{Direction, Dynamic, GameBuilder, Pcg32, Snake, VecGame};
+
+use crate::bot::Process;
+use crate::stats::{GameResult, Outcome};
+
+fn game() -> VecGame<Pcg32> {
+    GameBuilder::new(5, 4).seed(0).build::<Dynamic, Pcg32>()
+}
+
+/// A bot written in shell, `$0` is `argument`
+fn shell(script: &str, argument: &str) -> Vec<String> {
+    ["sh", "-c", script, argument].map(String::from).to_vec()
+}
+
+fn lost(game: &dyn Snake) -> GameResult {
+    GameResult {
+        seed: 0,
+        outcome: Outcome::Lost,
+        length: game.length(),
+        ticks: game.ticks(),
+        score: game.score(),
+        apples: 0,
+        slow_moves: 0,
+    }
+}
+
+#[test]
+fn bot_is_told_about_the_game_and_answers_every_tick() {
+    let path = std::env::temp_dir().join(format!("snake-sim-bot-{}", std::process::id()));
+    let script = r#"while read -r line; do echo "$line" >> "$0"; case "$line" in TICK*) echo down;; esac; done"#;
+    let mut game = game();
+    let mut bot = Process::start(&shell(script, path.to_str().unwrap()), &game).unwrap();
+
+    assert_eq!(Some(Direction::Down), bot.ask(&game, Duration::from_secs(5)));
+    game.set_direction(Direction::Down);
+    game.advance();
+    assert_eq!(Some(Direction::Down), bot.ask(&game, Duration::from_secs(5)));
+    assert_eq!(0, bot.slow_moves);
+    bot.finish(&lost(&game));
+
+    let seen = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    let lines: Vec<&str> = seen.lines().collect();
+    assert_eq!(
+        vec![
+            "GAME 5 4 wrap",
+            "TICK 0 0 right",
+            ".....",
+            ".....",
+            ".OHF.",
+            ".....",
+            "TICK 1 0 down",
+            ".....",
+            ".....",
+            "..OF.",
+            "..H..",
+            "OVER lost 0 2",
+        ],
+        lines
+    );
+}
+
+#[test]
+fn late_answers_count_as_slow_and_are_not_taken_for_the_next_tick() {
+    let script = r#"while read -r line; do case "$line" in
+        "TICK 0"*) echo up;;
+        "TICK 1"*) sleep 0.5; echo left;;
+        TICK*) echo down;;
+    esac; done"#;
+    let mut game = game();
+    let mut bot = Process::start(&shell(script, "bot"), &game).unwrap();
+
+    assert_eq!(Some(Direction::Up), bot.ask(&game, Duration::from_secs(5)));
+    game.advance();
+    assert_eq!(None, bot.ask(&game, Duration::from_millis(50)));
+    game.advance();
+    assert_eq!(Some(Direction::Down), bot.ask(&game, Duration::from_secs(5)));
+    assert_eq!(1, bot.slow_moves);
+    assert!(!bot.is_gone());
+    bot.stop();
+}
+
+#[test]
+fn bot_that_never_answers_is_given_up_on() {
+    let mut game = game();
+    let mut bot = Process::start(&shell("cat > /dev/null", "bot"), &game).unwrap();
+
+    for _ in 0..15 {
+        assert_eq!(None, bot.ask(&game, Duration::from_millis(10)));
+        game.advance();
+    }
+    assert_eq!(10, bot.slow_moves);
+    assert!(bot.is_gone());
+    bot.stop();
+}
+
+#[test]
+fn bot_that_stops_reading_does_not_hold_up_the_game() {
+    // About 10 KB a tick, the pipe is full after a few ticks
+    let game: VecGame<Pcg32> = GameBuilder::new(100, 100).build::<Dynamic, Pcg32>();
+    let mut bot = Process::start(&shell("sleep 30", "bot"), &game).unwrap();
+
+    let started = Instant::now();
+    for _ in 0..20 {
+        assert_eq!(None, bot.ask(&game, Duration::from_millis(10)));
+    }
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(bot.is_gone());
+    bot.stop();
+}
+
+#[test]
+fn bot_that_exits_is_gone() {
+    let game = game();
+    let mut bot = Process::start(&shell("exit 0", "bot"), &game).unwrap();
+
+    let started = Instant::now();
+    assert_eq!(None, bot.ask(&game, Duration::from_secs(5)));
+    assert!(bot.is_gone());
+    assert_eq!(0, bot.slow_moves);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    bot.stop();
+}
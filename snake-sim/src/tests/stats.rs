@@ -0,0 +1,170 @@
+use crate::stats::*;
+
+fn result(seed: u64, outcome: Outcome, length: usize, ticks: u32, score: u32) -> GameResult {
+    GameResult {
+        seed,
+        outcome,
+        length,
+        ticks,
+        score,
+        apples: score,
+        slow_moves: 0,
+    }
+}
+
+fn results() -> Vec<GameResult> {
+    vec![
+        result(0, Outcome::Won, 20, 100, 18),
+        result(1, Outcome::Lost, 4, 10, 2),
+        result(2, Outcome::Lost, 6, 40, 4),
+        result(3, Outcome::TimedOut, 10, 50, 8),
+    ]
+}
+
+#[test]
+fn summary_adds_up_every_game() {
+    let summary = Summary::of(&results());
+
+    assert_eq!((4, 1, 2, 1), (summary.games, summary.won, summary.lost, summary.timed_out));
+    assert_eq!(0.25, summary.win_rate());
+    assert_eq!(
+        Spread {
+            mean: 10.0,
+            min: 4.0,
+            median: 8.0,
+            max: 20.0
+        },
+        summary.length
+    );
+    assert_eq!(45.0, summary.ticks.median);
+    assert_eq!((8.0, 6.0), (summary.score.mean, summary.score.median));
+    // 32 apples in 200 ticks
+    assert_eq!(0.16, summary.apples_per_tick);
+}
+
+#[test]
+fn median_of_an_odd_number_of_games_is_the_middle_one() {
+    let summary = Summary::of(&results()[1..]);
+
+    assert_eq!(6.0, summary.length.median);
+    assert_eq!((4.0, 10.0), (summary.length.min, summary.length.max));
+    assert_eq!(0.0, summary.win_rate());
+}
+
+#[test]
+fn no_games_add_up_to_nothing() {
+    let summary = Summary::of(&[]);
+
+    assert_eq!(0, summary.games);
+    assert_eq!(Spread::default(), summary.length);
+    assert_eq!((0.0, 0.0), (summary.win_rate(), summary.apples_per_tick));
+}
+
+#[test]
+fn summary_is_written_as_csv_and_json() {
+    let summary = Summary::of(&results()[..2]);
+
+    assert_eq!(
+        concat!(
+            "games,won,lost,timed_out,",
+            "length_mean,length_min,length_median,length_max,",
+            "ticks_mean,ticks_min,ticks_median,ticks_max,",
+            "score_mean,score_min,score_median,score_max,apples_per_tick\n",
+            "2,1,1,0,12,4,12,20,55,10,55,100,10,2,10,18,0.18181818181818182\n",
+        ),
+        summary_of(Format::Csv, "t", &summary)
+    );
+    assert_eq!(
+        concat!(
+            r#"{"title":"greedy on 5x5","games":2,"won":1,"lost":1,"timed_out":0,"#,
+            r#""length":{"mean":12,"min":4,"median":12,"max":20},"#,
+            r#""ticks":{"mean":55,"min":10,"median":55,"max":100},"#,
+            r#""score":{"mean":10,"min":2,"median":10,"max":18},"#,
+            r#""apples_per_tick":0.18181818181818182}"#,
+            "\n",
+        ),
+        summary_of(Format::Json, "greedy on 5x5", &summary)
+    );
+}
+
+fn summary_of(format: Format, title: &str, summary: &Summary) -> String {
+    crate::stats::summary(format, title, summary)
+}
+
+#[test]
+fn games_are_written_one_by_one() {
+    let results = &results()[..2];
+
+    assert_eq!(
+        concat!(
+            "seed,outcome,length,ticks,score,apples,apples_per_tick\n",
+            "0,won,20,100,18,18,0.18\n",
+            "1,lost,4,10,2,2,0.2\n",
+        ),
+        games(Format::Csv, results)
+    );
+    assert_eq!(
+        concat!(
+            r#"[{"seed":0,"outcome":"won","length":20,"ticks":100,"score":18,"apples":18,"apples_per_tick":0.18},"#,
+            r#"{"seed":1,"outcome":"lost","length":4,"ticks":10,"score":2,"apples":2,"apples_per_tick":0.2}]"#,
+            "\n",
+        ),
+        games(Format::Json, results)
+    );
+}
+
+#[test]
+fn table_has_a_line_per_measurement() {
+    let table = summary_of(Format::Table, "greedy on 5x5", &Summary::of(&results()));
+    let lines: Vec<&str> = table.lines().collect();
+
+    assert_eq!("greedy on 5x5", lines[0]);
+    assert_eq!("4 games: 1 won, 2 lost, 1 timed out", lines[1]);
+    assert!(lines[3].starts_with("length") && lines[3].ends_with("10.0         4         8        20"));
+    assert_eq!("apples per tick 0.1600", lines[6]);
+}
+
+fn standing(bot: &str, results: &[GameResult]) -> Standing {
+    Standing {
+        bot: bot.into(),
+        summary: Summary::of(results),
+    }
+}
+
+#[test]
+fn bots_are_ranked_by_wins_then_length_then_timeouts() {
+    let standings = vec![
+        standing("short winner", &[result(0, Outcome::Won, 10, 50, 8), result(1, Outcome::Lost, 4, 9, 2)]),
+        standing("stuck", &[result(0, Outcome::TimedOut, 30, 99, 28), result(1, Outcome::Lost, 30, 99, 28)]),
+        standing("long winner", &[result(0, Outcome::Won, 12, 50, 10), result(1, Outcome::Lost, 4, 9, 2)]),
+        standing("long loser", &[result(0, Outcome::Lost, 30, 99, 28), result(1, Outcome::Lost, 30, 99, 28)]),
+    ];
+
+    let ranked: Vec<String> = rank(standings).into_iter().map(|s| s.bot).collect();
+    assert_eq!(vec!["long winner", "short winner", "long loser", "stuck"], ranked);
+}
+
+#[test]
+fn leaderboard_is_written_as_csv_and_json() {
+    let mut slow = results();
+    slow[1].slow_moves = 3;
+    let standings = vec![standing("greedy", &results()), standing("python3 \"bot\".py", &slow)];
+
+    assert_eq!(
+        concat!(
+            "rank,bot,games,won,win_rate,length_mean,score_mean,timed_out,slow_moves\n",
+            "1,\"greedy\",4,1,0.25,10,8,1,0\n",
+            "2,\"python3 \"\"bot\"\".py\",4,1,0.25,10,8,1,3\n",
+        ),
+        leaderboard(Format::Csv, "t", standings.clone())
+    );
+    assert_eq!(
+        concat!(
+            r#"{"title":"Tournament","standings":["#,
+            r#"{"rank":1,"bot":"greedy","games":4,"won":1,"win_rate":0.25,"length_mean":10,"score_mean":8,"timed_out":1,"slow_moves":0},"#,
+            r#"{"rank":2,"bot":"python3 \"bot\".py","games":4,"won":1,"win_rate":0.25,"length_mean":10,"score_mean":8,"timed_out":1,"slow_moves":3}]}"#,
+            "\n",
+        ),
+        leaderboard(Format::Json, "Tournament", standings)
+    );
+}
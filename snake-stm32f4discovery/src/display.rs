@@ -3,6 +3,7 @@ use embedded_graphics::prelude::*;
 use snake::*;
 use ssd1331::interface::DisplayInterface;
 use ssd1331::prelude::*;
+use ssd1331::properties::DisplayProperties;
 use core::borrow::BorrowMut;
 
 pub fn draw_rust_logo(disp: &mut GraphicsMode<impl DisplayInterface>) {
@@ -27,34 +28,38 @@ pub fn draw_rust_logo(disp: &mut GraphicsMode<impl DisplayInterface>) {
     disp.flush().unwrap();
 }
 
-pub fn draw_square(
-    disp: &mut GraphicsMode<impl DisplayInterface>,
-    size: usize,
-    x: usize,
-    y: usize,
-    color: u16,
-) {
-    for py in 0..size {
-        for px in 0..size {
-            disp.set_pixel(((x * size) + px) as u32, ((y * size) + py) as u32, color);
-        }
+/// Side of a board square in pixels
+const SQUARE: u8 = 4;
+
+/// Blanks the whole display, for a board that's drawn from scratch
+pub fn clear(disp: &mut DisplayProperties<impl DisplayInterface>) {
+    let row = [0; 96 * 2];
+    disp.set_draw_area((0, 0), (96, 64)).unwrap();
+    for _ in 0..64 {
+        disp.draw(&row).unwrap();
     }
 }
 
-pub fn draw_board(disp: &mut GraphicsMode<impl DisplayInterface>, board: &dyn Board) {
-    const SIZE: usize = 4;
+/// Draws the squares that changed, each in a window of its own so that only their pixels are
+/// sent to the display
+pub fn draw_changes(disp: &mut DisplayProperties<impl DisplayInterface>, changes: impl Iterator<Item = Change>) {
+    for Change { location: Location { x, y }, new, .. } in changes {
+        let color: u16 = match new {
+            Square::Snake | Square::Player(_) => 31,
+            Square::Fruit(FruitKind::Apple) => 2016,
+            Square::Fruit(FruitKind::Bonus) => 65504,
+            Square::Fruit(FruitKind::Feast) => 64800,
+            Square::Fruit(FruitKind::Shrink) => 30735,
+            Square::Wall => 33808,
+            Square::Empty => 0,
+        };
 
-    for (Location{x,y}, square) in board.iter() {
-        match square {
-            Square::Snake => {
-                draw_square(disp, SIZE, x as usize, y as usize, 31);
-            }
-            Square::Fruit => {
-                draw_square(disp, SIZE, x as usize, y as usize, 2016);
-            }
-            Square::Empty => {}
+        let mut pixels = [0; SQUARE as usize * SQUARE as usize * 2];
+        for pixel in pixels.chunks_mut(2) {
+            pixel.copy_from_slice(&color.to_be_bytes());
         }
+        let (x, y) = (x as u8 * SQUARE, y as u8 * SQUARE);
+        disp.set_draw_area((x, y), (x + SQUARE, y + SQUARE)).unwrap();
+        disp.draw(&pixels).unwrap();
     }
-
-    disp.flush().unwrap();
 }
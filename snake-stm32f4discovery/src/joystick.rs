@@ -1,3 +1,4 @@
+use embedded_hal::digital::InputPin;
 use stm32f4xx_hal::adc::{config::SampleTime, Adc};
 use stm32f4xx_hal::stm32::ADC1;
 
@@ -10,21 +11,68 @@ pub enum Direction {
     Center,
 }
 
-pub struct AdcJoystick<PINX, PINY> {
+/// What was done with the joystick button
+#[derive(Debug, PartialEq)]
+pub enum Gesture {
+    /// Pressed and released quickly
+    Press,
+    /// Held down for `HOLD_POLLS` polls, reported once while it is still down
+    Hold,
+}
+
+/// A second when polled by the 20Hz timer
+pub const HOLD_POLLS: u16 = 20;
+
+pub struct AdcJoystick<PINX, PINY, BUTTON> {
     pub adc: Adc<ADC1>,
     pub x: PINX,
     pub y: PINY,
+    /// Pulled up, low while pressed
+    pub button: BUTTON,
+    /// Polls the button has been down for
+    pub held: u16,
 }
 
 pub trait Joystick {
     fn read(&mut self) -> Direction;
+    /// Meant to be called at a fixed rate, see `HOLD_POLLS`
+    fn gesture(&mut self) -> Option<Gesture>;
 }
 
-impl<PINX, PINY> Joystick for AdcJoystick<PINX, PINY>
+impl<PINX, PINY, BUTTON> AdcJoystick<PINX, PINY, BUTTON>
+where
+    PINX: embedded_hal::adc::Channel<ADC1, ID = u8>,
+{
+    /// Seed made of the least significant bits of quick samples, they are mostly noise
+    pub fn noise(&mut self) -> u64 {
+        (0..64).fold(0, |seed, _| {
+            let sample = self.adc.convert(&self.x, SampleTime::Cycles_3);
+            (seed << 1) | u64::from(sample & 1)
+        })
+    }
+}
+
+impl<PINX, PINY, BUTTON> Joystick for AdcJoystick<PINX, PINY, BUTTON>
 where
     PINX: embedded_hal::adc::Channel<ADC1, ID = u8>,
     PINY: embedded_hal::adc::Channel<ADC1, ID = u8>,
+    BUTTON: InputPin,
 {
+    fn gesture(&mut self) -> Option<Gesture> {
+        if self.button.is_low() {
+            self.held = self.held.saturating_add(1);
+            return if self.held == HOLD_POLLS { Some(Gesture::Hold) } else { None };
+        }
+
+        let held = self.held;
+        self.held = 0;
+        if held > 0 && held < HOLD_POLLS {
+            Some(Gesture::Press)
+        } else {
+            None
+        }
+    }
+
     fn read(&mut self) -> Direction {
         let sample_x = self.adc.convert(&self.x, SampleTime::Cycles_480);
         let x = self.adc.sample_to_millivolts(sample_x);
@@ -1,4 +1,4 @@
-
+use stm32f4xx_hal::stm32::{GPIOD, RCC};
 
 /// All the user LEDs
 pub static LEDS: [Led; 4] = [
@@ -17,13 +17,13 @@ impl Led {
     /// Turns off the LED
     pub fn off(&self) {
         // NOTE(safe) atomic write
-        unsafe { (*GPIOD.get()).bsrr.write(|w| w.bits(1 << (self.i + 16))) }
+        unsafe { (*GPIOD::ptr()).bsrr.write(|w| w.bits(1 << (self.i + 16))) }
     }
 
     /// Turns on the LED
     pub fn on(&self) {
         // NOTE(safe) atomic write
-        unsafe { (*GPIOD.get()).bsrr.write(|w| w.bits(1 << self.i)) }
+        unsafe { (*GPIOD::ptr()).bsrr.write(|w| w.bits(1 << self.i)) }
     }
 }
 
@@ -32,7 +32,7 @@ pub fn init(gpiod: &GPIOD, rcc: &RCC) {
     // Power up peripherals
     rcc.ahb1enr.modify(|_, w| w.gpioden().enabled());
 
-    // Configure pins 8-15 as outputs
+    // Configure pins 12-15 as outputs
     gpiod.moder.modify(|_, w| {
         w.moder12()
             .output()
@@ -45,12 +45,17 @@ pub fn init(gpiod: &GPIOD, rcc: &RCC) {
     });
 }
 
-/// LED4, Orange
-pub fn orange() -> &'static Led {
-    &LEDS[0]
+/// Turns off all the user LEDs
+pub fn all_off() {
+    LEDS.iter().for_each(Led::off);
 }
-/// LED3, Green
+
+/// LED4, Green
 pub fn green() -> &'static Led {
+    &LEDS[0]
+}
+/// LED3, Orange
+pub fn orange() -> &'static Led {
     &LEDS[1]
 }
 /// LED5, Red
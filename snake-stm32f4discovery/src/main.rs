@@ -14,6 +14,7 @@ use embedded_hal::spi::{Mode, Phase, Polarity};
 use embedded_hal::timer::CountDown;
 
 use ssd1331::interface::DisplayInterface;
+use ssd1331::mode::displaymode::DisplayModeTrait;
 use ssd1331::prelude::*;
 use ssd1331::Builder;
 
@@ -26,56 +27,145 @@ use stm32f4xx_hal::time::U32Ext;
 use stm32f4xx_hal::timer;
 use stm32f4xx_hal::{delay::Delay, spi};
 
-use core::cell::RefCell;
+use core::cell::{Cell, RefCell};
 
 use snake::*;
 
 mod display;
 mod joystick;
-mod simple_rng;
+mod leds;
 
 use joystick::Joystick;
-use simple_rng::SimpleRNG;
 
-type Width = generic_array::typenum::U22;
-type Height = generic_array::typenum::U16;
-type Array<T> = generic_array::GenericArray<T, <Width as core::ops::Mul<Height>>::Output>;
-type SnakeType = Game<Array<Square>, Array<Location>, SimpleRNG>;
+type SnakeType = Game<{ 22 * 16 }, Pcg32>;
+
+/// The display refresh is slow, so the game starts slower than on the desktop
+const SPEED: Speed = Speed::new(300)
+    .curve(SpeedCurve::Linear { step_ms: 20 })
+    .per(SpeedMetric::Score, 3)
+    .min_interval_ms(100);
+
+/// Levels picked with the joystick while the game is paused, the first one is the open board
+const LEVELS: [Option<&str>; 3] = [
+    None,
+    Some(include_str!("../../levels/box-22x16.txt")),
+    Some(include_str!("../../levels/pillars-22x16.txt")),
+];
+
+/// Board edges picked with the joystick while the game is paused
+const BOUNDARY_MODES: [BoundaryMode; 4] = [
+    BoundaryMode::Wrap,
+    BoundaryMode::Solid,
+    BoundaryMode::WrapHorizontally,
+    BoundaryMode::WrapVertically,
+];
+
+/// The board plays by itself once the joystick has been left alone for 10 seconds, it's polled
+/// 20 times a second
+const DEMO_AFTER_POLLS: u32 = 20 * 10;
 
 type PC0Analog = gpioc::PC0<Analog>;
 type PC1Analog = gpioc::PC1<Analog>;
+type PC2PullUp = gpioc::PC2<Input<PullUp>>;
 
 static MUTEX_TIM2: Mutex<RefCell<Option<timer::Timer<TIM2>>>> = Mutex::new(RefCell::new(None));
 static MUTEX_GAME: Mutex<RefCell<Option<SnakeType>>> = Mutex::new(RefCell::new(None));
-static MUTEX_JOY: Mutex<RefCell<Option<joystick::AdcJoystick<PC0Analog, PC1Analog>>>> =
+static MUTEX_AUTOPILOT: Mutex<RefCell<Option<Greedy<Fixed<{ 22 * 16 }>>>>> = Mutex::new(RefCell::new(None));
+static IDLE_POLLS: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+static LEVEL: Mutex<Cell<usize>> = Mutex::new(Cell::new(0));
+static BOUNDARY_MODE: Mutex<Cell<usize>> = Mutex::new(Cell::new(0));
+/// Whether the joystick was pushed to a side at the previous poll
+static PUSHED: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+/// Set when a new game took the place of the one on the display
+static NEW_GAME: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+static MUTEX_JOY: Mutex<RefCell<Option<joystick::AdcJoystick<PC0Analog, PC1Analog, PC2PullUp>>>> =
     Mutex::new(RefCell::new(None));
 
 #[interrupt]
 fn TIM2() {
     free(|cs| {
         MUTEX_TIM2 .borrow(cs) .borrow_mut() .as_mut() .unwrap() .wait() .ok();
-        let direction = MUTEX_JOY.borrow(cs).borrow_mut().as_mut().unwrap().read();
-        if let Some(d) = match direction {
+        let mut joy = MUTEX_JOY.borrow(cs).borrow_mut();
+        let joy = joy.as_mut().unwrap();
+        let mut game = MUTEX_GAME.borrow(cs).borrow_mut();
+        // Nothing to control while the logo is shown
+        let game = match game.as_mut() {
+            Some(game) => game,
+            None => return,
+        };
+
+        let direction = match joy.read() {
             joystick::Direction::Left => Some(snake::Direction::Left),
             joystick::Direction::Right => Some(snake::Direction::Right),
             joystick::Direction::Up => Some(snake::Direction::Up),
             joystick::Direction::Down => Some(snake::Direction::Down),
             joystick::Direction::Center => None,
-        } {
-            MUTEX_GAME
-                .borrow(cs)
-                .borrow_mut()
-                .as_mut()
-                .unwrap()
-                .set_direction(d);
+        };
+        if let Some(d) = direction {
+            game.set_direction(d);
+        }
+
+        let gesture = joy.gesture();
+        let idle = IDLE_POLLS.borrow(cs);
+        match (direction, gesture) {
+            (None, None) => idle.set(idle.get().saturating_add(1)),
+            _ => idle.set(0),
+        }
+
+        // Pressing the joystick pauses and resumes, holding it or pressing it after the game
+        // is over starts a new one
+        match (gesture, game.status()) {
+            (Some(joystick::Gesture::Hold), _) => game.restart(),
+            (Some(joystick::Gesture::Press), GameStatus::Lost | GameStatus::Won) => game.restart(),
+            (Some(joystick::Gesture::Press), GameStatus::Paused) => game.resume(),
+            (Some(joystick::Gesture::Press), GameStatus::InProgress) => game.pause(),
+            (None, _) => {}
+        }
+
+        // While the game is paused pushing the joystick left or right picks the level and up
+        // or down the board edges, the game starts over with them and stays paused
+        let pushed = PUSHED.borrow(cs);
+        let just_pushed = direction.is_some() && !pushed.get();
+        pushed.set(direction.is_some());
+        if just_pushed && game.status() == GameStatus::Paused {
+            let (level, boundary_mode) = (LEVEL.borrow(cs), BOUNDARY_MODE.borrow(cs));
+            let (choice, count, forward) = match direction {
+                Some(snake::Direction::Left) => (level, LEVELS.len(), false),
+                Some(snake::Direction::Right) => (level, LEVELS.len(), true),
+                Some(snake::Direction::Down) => (boundary_mode, BOUNDARY_MODES.len(), false),
+                Some(snake::Direction::Up) => (boundary_mode, BOUNDARY_MODES.len(), true),
+                None => return,
+            };
+            let step = if forward { 1 } else { count - 1 };
+            choice.set((choice.get() + step) % count);
+            *game = new_game(joy.noise(), level.get(), boundary_mode.get());
+            game.pause();
+            NEW_GAME.borrow(cs).set(true);
         }
     });
 }
 
+fn new_game(seed: u64, level: usize, boundary_mode: usize) -> SnakeType {
+    let level = LEVELS[level].map(|text| Level::parse(text).expect("bundled levels are valid"));
+    create_game_instance!(22, 16, Pcg32, |b| {
+        let b = b
+            .seed(seed)
+            .fruit_policy(FruitPolicy::mixed())
+            .boundary_mode(BOUNDARY_MODES[boundary_mode]);
+        match &level {
+            Some(level) => b.level(level),
+            None => b,
+        }
+    })
+}
+
 pub fn init() -> (Delay, GraphicsMode<impl DisplayInterface>) {
     let cp = cortex_m::Peripherals::take().unwrap();
     let dp = stm32f4xx_hal::stm32::Peripherals::take().unwrap();
 
+    // LEDS
+    leds::init(&dp.GPIOD, &dp.RCC);
+
     let rcc = dp.RCC.constrain();
 
     let clocks = rcc.cfgr.freeze();
@@ -125,11 +215,14 @@ pub fn init() -> (Delay, GraphicsMode<impl DisplayInterface>) {
     let gpioc = dp.GPIOC.split();
     let pc0 = gpioc.pc0.into_analog();
     let pc1 = gpioc.pc1.into_analog();
+    let pc2 = gpioc.pc2.into_pull_up_input();
 
     let joystick = joystick::AdcJoystick {
         adc,
         x: pc0,
         y: pc1,
+        button: pc2,
+        held: 0,
     };
 
     // TIMER INTERRUPT
@@ -154,10 +247,12 @@ pub fn init() -> (Delay, GraphicsMode<impl DisplayInterface>) {
 pub fn init_game(display: &mut GraphicsMode<impl DisplayInterface>, delay: &mut Delay) {
     display::draw_rust_logo(display);
     delay.delay_ms(3000_u16);
+    display.clear();
+    display.flush().unwrap();
     free(|cs| {
-        MUTEX_GAME
-            .borrow(cs)
-            .replace(Some(create_game_instance!(22, 16, SimpleRNG)));
+        let seed = MUTEX_JOY.borrow(cs).borrow_mut().as_mut().unwrap().noise();
+        MUTEX_GAME.borrow(cs).replace(Some(new_game(seed, 0, 0)));
+        MUTEX_AUTOPILOT.borrow(cs).replace(Some(Greedy::new(22, 16)));
     });
 }
 
@@ -166,21 +261,35 @@ fn main() -> ! {
     let (mut delay, mut display) = init();
 
     init_game(&mut display, &mut delay);
+    // From now on only the squares that changed are drawn, straight to the display
+    let mut display = display.release();
 
     loop {
-        let status = free(|cs| {
+        let interval = free(|cs| {
             let mut game = MUTEX_GAME.borrow(cs).borrow_mut();
-            let status = game.as_mut().unwrap().advance();
-            let board = game.as_mut().unwrap().board();
-            display.clear();
-            display::draw_board(&mut display, board);
-            status
+            let game = game.as_mut().unwrap();
+            if IDLE_POLLS.borrow(cs).get() >= DEMO_AFTER_POLLS {
+                if matches!(game.status(), GameStatus::Lost | GameStatus::Won) {
+                    game.restart();
+                }
+                let mut autopilot = MUTEX_AUTOPILOT.borrow(cs).borrow_mut();
+                autopilot.as_mut().unwrap().steer(game);
+            }
+            leds::all_off();
+            game.advance_with_events(&mut |event| match event {
+                GameEvent::FruitEaten { .. } => leds::green().on(),
+                GameEvent::DirectionChanged { .. } => leds::blue().on(),
+                GameEvent::Collided { .. } => leds::red().on(),
+                GameEvent::Won => leds::LEDS.iter().for_each(leds::Led::on),
+                _ => {}
+            });
+            if NEW_GAME.borrow(cs).replace(false) {
+                display::clear(&mut display);
+            }
+            display::draw_changes(&mut display, game.changes());
+            SPEED.interval_ms(&*game)
         });
 
-        if status == GameStatus::Lost {
-            init_game(&mut display, &mut delay);
-        }
-
-        delay.delay_ms(300_u16);
+        delay.delay_ms(interval);
     }
 }
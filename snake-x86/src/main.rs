@@ -1,73 +1,403 @@
+use cursive::event::{Event, Key};
 use cursive::traits::*;
-use cursive::views::{Canvas, OnEventView};
-use cursive::{Cursive, CursiveExt};
-use snake::{Direction, Game, Location, Snake};
+use cursive::views::{Canvas, LinearLayout, OnEventView};
+use cursive::Cursive;
+use snake::{
+    BoundaryMode, Change, Controller, Direction, Dynamic, FruitKind, FruitPolicy, GameStatus,
+    Greedy, Level, Location, Pcg32, Recorder, Replay, ReplayStart, Snake, Speed, Square,
+    StreamWriter, VecGame,
+};
 
+use std::fs::File;
+use std::io::BufWriter;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Default)]
-pub struct RNG {}
+mod online;
+mod two_player;
+mod watch;
 
-impl snake::RandomNumberGenerator for RNG {
-    fn next(&mut self) -> u32 {
-        rand::random::<u32>()
+/// Borrows the save or the level it started from until the replay is written
+type GameRecorder<'a> = Recorder<'a, Dynamic, Pcg32, Vec<u8>>;
+
+fn glyph(square: Square) -> &'static str {
+    match square {
+        Square::Snake | Square::Player(0) => "O",
+        Square::Player(_) => "X",
+        Square::Fruit(FruitKind::Apple) => "F",
+        Square::Fruit(FruitKind::Bonus) => "$",
+        Square::Fruit(FruitKind::Feast) => "%",
+        Square::Fruit(FruitKind::Shrink) => "-",
+        Square::Empty => " ",
+        Square::Wall => "#",
+    }
+}
+
+fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|a| a != name).nth(1)
+}
+
+fn fail(message: std::fmt::Arguments) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+fn boundary_mode_from_args() -> BoundaryMode {
+    match arg_value("--boundary").as_deref() {
+        None | Some("wrap") => BoundaryMode::Wrap,
+        Some("solid") => BoundaryMode::Solid,
+        Some("horizontal") => BoundaryMode::WrapHorizontally,
+        Some("vertical") => BoundaryMode::WrapVertically,
+        Some(other) => fail(format_args!(
+            "Unknown boundary mode '{}', expected wrap, solid, horizontal or vertical",
+            other
+        )),
+    }
+}
+
+fn fruit_policy_from_args() -> FruitPolicy {
+    match arg_value("--fruit").as_deref() {
+        None | Some("apples") => FruitPolicy::new(),
+        Some("mixed") => FruitPolicy::mixed(),
+        Some(other) => fail(format_args!(
+            "Unknown fruit '{}', expected apples or mixed",
+            other
+        )),
+    }
+}
+
+fn size_from_args() -> Option<(usize, usize)> {
+    let size = arg_value("--size")?;
+    let parsed = size
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+
+    match parsed {
+        Some((width, height)) if width >= 3 && height >= 1 => Some((width, height)),
+        _ => fail(format_args!(
+            "Invalid board size '{}', expected WIDTHxHEIGHT of at least 3x1",
+            size
+        )),
+    }
+}
+
+fn save_path() -> std::path::PathBuf {
+    match arg_value("--save") {
+        Some(path) => path.into(),
+        None => std::env::var_os("HOME")
+            .map(std::path::PathBuf::from)
+            .unwrap_or_default()
+            .join(".snake-x86.save"),
+    }
+}
+
+/// Options that set up a new game, a resumed one keeps what it was saved with
+const NEW_GAME_OPTIONS: [&str; 4] = ["--level", "--size", "--fruit", "--boundary"];
+
+/// The game saved when the previous one was quit, unless a new game is asked for
+fn read_save() -> Option<Vec<u8>> {
+    if std::env::args().any(|a| a == "--new") {
+        return None;
+    }
+    let path = save_path();
+    let bytes = std::fs::read(&path).ok()?;
+    if let Some(option) = std::env::args().find(|a| NEW_GAME_OPTIONS.contains(&a.as_str())) {
+        fail(format_args!(
+            "{} can't change the game saved in {}, start with --new to play a new one",
+            option,
+            path.display()
+        ));
+    }
+    Some(bytes)
+}
+
+fn read_level(path: &str) -> String {
+    std::fs::read_to_string(path).unwrap_or_else(|e| fail(format_args!("{}: {}", path, e)))
+}
+
+/// Continues the game in `save` or starts a new one on the level at `level_path` with the
+/// text `level`, the save is used only once
+///
+/// `terminal_size` is only asked for when nothing else decides how big the board is.
+fn create_game<'a>(
+    save: Option<&'a [u8]>,
+    level_path: Option<&str>,
+    level: Option<&'a str>,
+    terminal_size: impl FnOnce() -> (usize, usize),
+) -> GameRecorder<'a> {
+    if let Some(bytes) = save {
+        let path = save_path();
+        let game = Recorder::from_snapshot(bytes).unwrap_or_else(|e| {
+            fail(format_args!(
+                "{}: {}, start with --new to ignore it",
+                path.display(),
+                e
+            ))
+        });
+        std::fs::remove_file(&path).ok();
+        return game;
+    }
+
+    let parsed = level.map(|text| {
+        Level::parse(text)
+            .unwrap_or_else(|e| fail(format_args!("{}:{}", level_path.unwrap_or_default(), e)))
+    });
+    let (width, height) = match &parsed {
+        Some(level) => (level.width(), level.height()),
+        None => size_from_args().unwrap_or_else(terminal_size),
+    };
+
+    Recorder::from_start(ReplayStart::New {
+        width,
+        height,
+        boundary_mode: boundary_mode_from_args(),
+        seed: rand::random(),
+        level,
+        fruit_policy: fruit_policy_from_args(),
+    })
+    .unwrap_or_else(|e| fail(format_args!("Can't start the game: {}", e)))
+}
+
+fn players_from_args() -> usize {
+    match arg_value("--players").as_deref() {
+        None | Some("1") => 1,
+        Some("2") => 2,
+        Some(other) => fail(format_args!("Unsupported number of players '{}', expected 1 or 2", other)),
+    }
+}
+
+fn start_terminal() -> Box<dyn cursive::backend::Backend> {
+    cursive::backends::try_default()
+        .unwrap_or_else(|e| fail(format_args!("Can't start the terminal UI: {}", e)))
+}
+
+fn verify_replay(path: &str) {
+    let bytes = std::fs::read(path).unwrap_or_else(|e| fail(format_args!("{}: {}", path, e)));
+    let game: VecGame<Pcg32> = Replay::parse(&bytes)
+        .and_then(|replay| replay.play())
+        .unwrap_or_else(|e| fail(format_args!("{}: {}", path, e)));
+
+    println!(
+        "{}: {:?} after {} ticks, score {} length {}",
+        path,
+        game.status(),
+        game.ticks(),
+        game.score(),
+        game.length()
+    );
+}
+/// What the keys ask the thread playing the game to do
+#[derive(Clone, Copy)]
+enum Command {
+    Turn(Direction),
+    Pause,
+    Restart,
+    /// Turns the demo mode on or off
+    Demo,
+    Quit,
+}
+
+/// What the screen shows of the game, kept up to date by the thread playing it
+struct Shown {
+    frame: Vec<&'static str>,
+    status: String,
+}
+
+impl Shown {
+    fn update(&mut self, game: &mut GameRecorder, demo: bool, speed: &Speed) {
+        let width = game.board().width();
+        for Change {
+            location: Location { x, y },
+            new,
+            ..
+        } in game.changes()
+        {
+            self.frame[y as usize * width + x as usize] = glyph(new);
+        }
+
+        let status = match game.status() {
+            _ if demo => "Demo ",
+            GameStatus::InProgress => "",
+            GameStatus::Paused => "Paused ",
+            GameStatus::Lost => "Lost ",
+            GameStatus::Won => "Won ",
+        };
+        self.status = format!(
+            "{}Level {} Score {} Length {}",
+            status,
+            speed.level(&*game),
+            game.score(),
+            game.length()
+        );
+    }
+}
+
+/// Plays `game` until told to quit and hands it back, every tick goes to `stream` which stops
+/// at the first error writing it
+///
+/// In the demo mode the game plays itself and starts over when it ends, any arrow key takes
+/// over the snake.
+fn play<'a>(
+    mut game: GameRecorder<'a>,
+    commands: Receiver<Command>,
+    shown: &Mutex<Shown>,
+    mut stream: Option<StreamWriter<BufWriter<File>>>,
+    mut demo: bool,
+) -> GameRecorder<'a> {
+    let speed = Speed::default();
+    let mut autopilot = Greedy::<Dynamic>::new(game.board().width(), game.board().height());
+    let mut next_tick = Instant::now();
+
+    loop {
+        shown.lock().unwrap().update(&mut game, demo, &speed);
+
+        match commands.recv_timeout(next_tick.saturating_duration_since(Instant::now())) {
+            Ok(Command::Turn(direction)) => {
+                demo = false;
+                game.set_direction(direction);
+            }
+            Ok(Command::Pause) => match game.status() {
+                GameStatus::Paused => game.resume(),
+                _ => game.pause(),
+            },
+            Ok(Command::Restart) => game.restart(),
+            Ok(Command::Demo) => demo = !demo,
+            Ok(Command::Quit) | Err(RecvTimeoutError::Disconnected) => return game,
+            Err(RecvTimeoutError::Timeout) => {
+                if demo {
+                    if matches!(game.status(), GameStatus::Lost | GameStatus::Won) {
+                        game.restart();
+                    }
+                    autopilot.steer(&mut game);
+                }
+                game.advance();
+                if stream.as_mut().is_some_and(|s| s.write(&game).is_err()) {
+                    stream = None;
+                }
+                next_tick = Instant::now() + Duration::from_millis(speed.interval_ms(&game).into());
+            }
+        }
     }
 }
 
 fn main() {
-    let game = std::sync::Arc::new(std::sync::Mutex::new(
-        Game::<{ 20 * 20 }, RNG>::new(20, 20),
-    ));
+    if let Some(path) = arg_value("--verify") {
+        verify_replay(&path);
+        return;
+    }
+
+    if let Some(address) = arg_value("--connect") {
+        online::play_online(&address);
+        return;
+    }
+
+    if let Some(path) = arg_value("--watch") {
+        watch::watch(&path);
+        return;
+    }
+
+    if players_from_args() == 2 {
+        two_player::play_together();
+        return;
+    }
+
+    // The recorder borrows these until the replay is written at exit
+    let save = read_save();
+    let level_path = arg_value("--level");
+    let level = level_path.as_deref().map(read_level);
+
+    let mut backend = None;
+    let game = create_game(
+        save.as_deref(),
+        level_path.as_deref(),
+        level.as_deref(),
+        || {
+            let size = backend.insert(start_terminal()).screen_size();
+            // The last row shows the score
+            (size.x.max(3), size.y.saturating_sub(1).max(1))
+        },
+    );
+    let backend = backend.unwrap_or_else(start_terminal);
+    let (width, height) = (game.board().width(), game.board().height());
+
+    let stream = arg_value("--stream").map(|path| {
+        File::create(&path)
+            .and_then(|file| StreamWriter::new(BufWriter::new(file), width, height))
+            .unwrap_or_else(|e| fail(format_args!("{}: {}", path, e)))
+    });
+
+    let shown = Arc::new(Mutex::new(Shown {
+        frame: vec![" "; width * height],
+        status: String::new(),
+    }));
+    let (commands, received) = mpsc::channel();
     let mut siv = Cursive::default();
 
     siv.add_global_callback('q', |s| s.quit());
-    siv.add_global_callback(cursive::event::Key::Left, {
-        let g = game.clone();
-        move |_| g.lock().unwrap().set_direction(Direction::Left)
-    });
-    siv.add_global_callback(cursive::event::Key::Up, {
-        let g = game.clone();
-        move |_| g.lock().unwrap().set_direction(Direction::Up)
-    });
-    siv.add_global_callback(cursive::event::Key::Down, {
-        let g = game.clone();
-        move |_| g.lock().unwrap().set_direction(Direction::Down)
-    });
-    siv.add_global_callback(cursive::event::Key::Right, {
-        let g = game.clone();
-        move |_| g.lock().unwrap().set_direction(Direction::Right)
-    });
+    let keys: [(Event, Command); 7] = [
+        ('p'.into(), Command::Pause),
+        ('r'.into(), Command::Restart),
+        ('a'.into(), Command::Demo),
+        (Key::Left.into(), Command::Turn(Direction::Left)),
+        (Key::Up.into(), Command::Turn(Direction::Up)),
+        (Key::Down.into(), Command::Turn(Direction::Down)),
+        (Key::Right.into(), Command::Turn(Direction::Right)),
+    ];
+    for (event, command) in keys {
+        let c = commands.clone();
+        siv.add_global_callback(event, move |_| {
+            c.send(command).ok();
+        });
+    }
 
     siv.add_layer(OnEventView::new(
-        Canvas::new(())
-            .with_draw({
-                let g = game.clone();
-                move |_, p| {
-                    let mut game = g.lock().unwrap();
-                    for (Location { x, y }, s) in game.board().iter() {
-                        p.print(
-                            (x, y),
-                            match s {
-                                snake::Square::Snake => "O",
-                                snake::Square::Fruit => "F",
-                                snake::Square::Empty => " ",
-                            },
-                        );
-                    }
-                }
-            })
-            .fixed_size((20, 20)),
+        LinearLayout::vertical()
+            .child(
+                Canvas::new(())
+                    .with_draw({
+                        let s = shown.clone();
+                        // Cursive repaints every view on each frame, so changed squares are
+                        // applied to a cached frame which is then printed as a whole
+                        move |_, p| {
+                            for (i, square) in s.lock().unwrap().frame.iter().enumerate() {
+                                p.print((i % width, i / width), square);
+                            }
+                        }
+                    })
+                    .fixed_size((width, height)),
+            )
+            .child(
+                Canvas::new(())
+                    .with_draw({
+                        let s = shown.clone();
+                        move |_, p| p.print((0, 0), &s.lock().unwrap().status)
+                    })
+                    .fixed_size((width, 1)),
+            ),
     ));
 
-    thread::spawn({
-        let g = game.clone();
-        move || loop {
-            g.lock().unwrap().advance();
-            std::thread::sleep(std::time::Duration::from_millis(200));
-        }
+    siv.set_fps(60);
+
+    let demo = std::env::args().any(|a| a == "--demo");
+    let game = thread::scope(|scope| {
+        let player = scope.spawn(|| play(game, received, &shown, stream, demo));
+        siv.runner(backend).run();
+        commands.send(Command::Quit).ok();
+        player.join().unwrap()
     });
 
-    siv.set_fps(60);
+    if matches!(game.status(), GameStatus::InProgress | GameStatus::Paused) {
+        let path = save_path();
+        std::fs::write(&path, game.game().to_snapshot())
+            .unwrap_or_else(|e| fail(format_args!("{}: {}", path.display(), e)));
+    }
 
-    siv.run();
+    if let Some(path) = arg_value("--record") {
+        game.to_bytes()
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| std::fs::write(&path, bytes).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| fail(format_args!("{}: {}", path, e)));
+    }
 }
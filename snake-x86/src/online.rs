@@ -0,0 +1,195 @@
+//! Playing a game hosted by `snake-server`.
+
+use cursive::traits::*;
+use cursive::views::{Canvas, LinearLayout};
+use cursive::Cursive;
+use snake::{Direction, Location, Square};
+
+use snake_server::{ClientMessage, ServerMessage};
+
+use std::io::{BufRead, BufReader, Write};
+use std::thread;
+
+use crate::{arg_value, fail, glyph, start_terminal};
+
+/// What the client knows about a game hosted by `snake-server`
+struct Online {
+    player: usize,
+    width: usize,
+    height: usize,
+    frame: Vec<&'static str>,
+    names: Vec<String>,
+    scores: Vec<u32>,
+    status: String,
+}
+
+impl Online {
+    /// Where `location` is in `frame`, `None` when it's off the board
+    fn index(&self, Location { x, y }: Location) -> Option<usize> {
+        let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+        (x < self.width && y < self.height).then(|| y * self.width + x)
+    }
+
+    /// Players and squares the game doesn't have are left out, like lines that don't parse
+    fn receive(&mut self, message: ServerMessage) {
+        match message {
+            ServerMessage::Welcome { .. } => {}
+            ServerMessage::Player { player, name } => {
+                if let Some(known) = self.names.get_mut(player) {
+                    *known = name;
+                }
+            }
+            ServerMessage::Start => {
+                self.frame.fill(" ");
+                self.scores.fill(0);
+                self.status.clear();
+            }
+            ServerMessage::Tick { changes, .. } => {
+                for (location, square) in changes {
+                    let Some(i) = self.index(location) else {
+                        continue;
+                    };
+                    self.frame[i] = match square {
+                        Square::Player(n) if usize::from(n) == self.player => "O",
+                        Square::Player(_) => "X",
+                        square => glyph(square),
+                    };
+                }
+            }
+            ServerMessage::Score { player, score } => {
+                if let Some(known) = self.scores.get_mut(player) {
+                    *known = score;
+                }
+            }
+            ServerMessage::Dead { player } if player == self.player => {
+                self.status = "Crashed ".into()
+            }
+            ServerMessage::Dead { .. } => {}
+            ServerMessage::Over { winner: None } => self.status = "Draw ".into(),
+            ServerMessage::Over {
+                winner: Some(player),
+            } => {
+                if let Some(name) = self.names.get(player) {
+                    self.status = format!("{} wins ", name);
+                }
+            }
+            ServerMessage::Bye { reason } => self.status = format!("Disconnected: {} ", reason),
+        }
+    }
+}
+
+/// Joins a game hosted by `snake-server`, the server moves the snakes and this only sends turns
+pub fn play_online(address: &str) {
+    let fail_to_connect =
+        |e: &dyn std::fmt::Display| -> ! { fail(format_args!("{}: {}", address, e)) };
+    let mut stream = std::net::TcpStream::connect(address).unwrap_or_else(|e| fail_to_connect(&e));
+    stream.set_nodelay(true).ok();
+    let mut reader = BufReader::new(stream.try_clone().unwrap_or_else(|e| fail_to_connect(&e)));
+
+    let name = arg_value("--name")
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| "player".into());
+    writeln!(stream, "{}", ClientMessage::Join { name }).unwrap_or_else(|e| fail_to_connect(&e));
+
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .unwrap_or_else(|e| fail_to_connect(&e));
+    let (player, players, width, height) = match line.trim().parse() {
+        Ok(ServerMessage::Welcome {
+            player,
+            players,
+            width,
+            height,
+        }) if player < players && width.checked_mul(height).is_some() => {
+            (player, players, width, height)
+        }
+        Ok(ServerMessage::Bye { reason }) => fail_to_connect(&reason),
+        Ok(_) | Err(_) => fail_to_connect(&"not a snake server"),
+    };
+
+    let online = std::sync::Arc::new(std::sync::Mutex::new(Online {
+        player,
+        width,
+        height,
+        frame: vec![" "; width * height],
+        names: vec![String::new(); players],
+        scores: vec![0; players],
+        status: "Waiting for players ".into(),
+    }));
+    thread::spawn({
+        let o = online.clone();
+        move || {
+            for line in reader.lines() {
+                let Ok(line) = line else { break };
+                if let Ok(message) = line.trim().parse() {
+                    o.lock().unwrap().receive(message);
+                }
+            }
+        }
+    });
+
+    let stream = std::sync::Arc::new(std::sync::Mutex::new(stream));
+    let mut siv = Cursive::default();
+
+    siv.add_global_callback('q', {
+        let s = stream.clone();
+        move |siv| {
+            writeln!(s.lock().unwrap(), "{}", ClientMessage::Quit).ok();
+            siv.quit()
+        }
+    });
+    let keys = [
+        (cursive::event::Key::Left, Direction::Left),
+        (cursive::event::Key::Up, Direction::Up),
+        (cursive::event::Key::Down, Direction::Down),
+        (cursive::event::Key::Right, Direction::Right),
+    ];
+    for (key, direction) in keys {
+        let s = stream.clone();
+        siv.add_global_callback(key, move |_| {
+            writeln!(s.lock().unwrap(), "{}", ClientMessage::Turn(direction)).ok();
+        });
+    }
+
+    siv.add_layer(
+        LinearLayout::vertical()
+            .child(
+                Canvas::new(())
+                    .with_draw({
+                        let o = online.clone();
+                        move |_, p| {
+                            for (i, s) in o.lock().unwrap().frame.iter().enumerate() {
+                                p.print((i % width, i / width), s);
+                            }
+                        }
+                    })
+                    .fixed_size((width, height)),
+            )
+            .child(
+                Canvas::new(())
+                    .with_draw({
+                        let o = online.clone();
+                        move |_, p| {
+                            let online = o.lock().unwrap();
+                            let scores: Vec<String> = online
+                                .names
+                                .iter()
+                                .zip(&online.scores)
+                                .enumerate()
+                                .map(|(i, (name, score))| match i == online.player {
+                                    true => format!("O {} {}", name, score),
+                                    false => format!("{} {}", name, score),
+                                })
+                                .collect();
+                            p.print((0, 0), &format!("{}{}", online.status, scores.join("  ")));
+                        }
+                    })
+                    .fixed_size((width, 1)),
+            ),
+    );
+
+    siv.set_fps(60);
+
+    siv.runner(start_terminal()).run();
+}
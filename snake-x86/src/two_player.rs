@@ -0,0 +1,154 @@
+//! Two players on one keyboard.
+
+use cursive::traits::*;
+use cursive::views::{Canvas, LinearLayout};
+use cursive::Cursive;
+use snake::{
+    Change, Direction, GameBuilder, Level, Location, MultiStatus, Pcg32, Speed, Square,
+    VecMultiGame,
+};
+
+use std::cell::RefCell;
+use std::thread;
+
+use crate::{
+    arg_value, boundary_mode_from_args, fail, glyph, read_level, size_from_args, start_terminal,
+};
+
+type TwoPlayerGame = VecMultiGame<Pcg32, 2>;
+
+/// Two snakes on one board, there is nothing to save or record for them
+fn create_two_player_game(terminal_size: impl FnOnce() -> (usize, usize)) -> TwoPlayerGame {
+    let level = arg_value("--level").map(|path| (read_level(&path), path));
+    let builder = match &level {
+        Some((text, path)) => {
+            let level = Level::parse(text).unwrap_or_else(|e| fail(format_args!("{}:{}", path, e)));
+            GameBuilder::new(0, 0).level(&level)
+        }
+        None => {
+            let (width, height) = size_from_args().unwrap_or_else(terminal_size);
+            GameBuilder::new(width, height)
+        }
+    };
+
+    builder
+        .seed(rand::random())
+        .boundary_mode(boundary_mode_from_args())
+        .try_build_multi()
+        .unwrap_or_else(|e| fail(format_args!("Can't start a two player game: {}", e)))
+}
+
+/// Player 1 steers with WASD and player 2 with the arrow keys
+pub fn play_together() {
+    let mut backend = None;
+    let game = create_two_player_game(|| {
+        let size = backend.insert(start_terminal()).screen_size();
+        // The last row shows the scores
+        (size.x.max(3), size.y.saturating_sub(1).max(1))
+    });
+    let backend = backend.unwrap_or_else(start_terminal);
+    let (width, height) = (game.board().width(), game.board().height());
+
+    let game = std::sync::Arc::new(std::sync::Mutex::new(game));
+    let mut siv = Cursive::default();
+
+    siv.add_global_callback('q', |s| s.quit());
+    siv.add_global_callback('p', {
+        let g = game.clone();
+        move |_| {
+            let mut game = g.lock().unwrap();
+            match game.status() {
+                MultiStatus::Paused => game.resume(),
+                _ => game.pause(),
+            }
+        }
+    });
+    siv.add_global_callback('r', {
+        let g = game.clone();
+        move |_| g.lock().unwrap().restart()
+    });
+
+    let keys: [(cursive::event::Event, usize, Direction); 8] = [
+        ('a'.into(), 0, Direction::Left),
+        ('w'.into(), 0, Direction::Up),
+        ('s'.into(), 0, Direction::Down),
+        ('d'.into(), 0, Direction::Right),
+        (cursive::event::Key::Left.into(), 1, Direction::Left),
+        (cursive::event::Key::Up.into(), 1, Direction::Up),
+        (cursive::event::Key::Down.into(), 1, Direction::Down),
+        (cursive::event::Key::Right.into(), 1, Direction::Right),
+    ];
+    for (event, player, direction) in keys {
+        let g = game.clone();
+        siv.add_global_callback(event, move |_| {
+            g.lock().unwrap().set_direction(player, direction)
+        });
+    }
+
+    siv.add_layer(
+        LinearLayout::vertical()
+            .child(
+                Canvas::new(RefCell::new(vec![" "; width * height]))
+                    .with_draw({
+                        let g = game.clone();
+                        move |frame, p| {
+                            let mut frame = frame.borrow_mut();
+                            let mut game = g.lock().unwrap();
+                            for Change {
+                                location: Location { x, y },
+                                new,
+                                ..
+                            } in game.changes()
+                            {
+                                frame[y as usize * width + x as usize] = glyph(new);
+                            }
+                            for (i, s) in frame.iter().enumerate() {
+                                p.print((i % width, i / width), s);
+                            }
+                        }
+                    })
+                    .fixed_size((width, height)),
+            )
+            .child(
+                Canvas::new(())
+                    .with_draw({
+                        let g = game.clone();
+                        move |_, p| {
+                            let game = g.lock().unwrap();
+                            let status = match game.status() {
+                                MultiStatus::InProgress => String::new(),
+                                MultiStatus::Paused => "Paused ".into(),
+                                MultiStatus::Finished { winner: None } => "Draw ".into(),
+                                MultiStatus::Finished {
+                                    winner: Some(player),
+                                } => format!("{} wins ", glyph(Square::Player(player as u8))),
+                            };
+                            p.print(
+                                (0, 0),
+                                &format!(
+                                    "{}O (WASD) {}  X (arrows) {}",
+                                    status,
+                                    game.score(0),
+                                    game.score(1)
+                                ),
+                            );
+                        }
+                    })
+                    .fixed_size((width, 1)),
+            ),
+    );
+
+    // Both snakes share the board so the game stays at the starting speed
+    let interval = Speed::default().interval_at(1);
+    thread::spawn({
+        let g = game.clone();
+        move || loop {
+            g.lock().unwrap().advance();
+            std::thread::sleep(std::time::Duration::from_millis(interval.into()));
+        }
+    });
+
+    siv.set_fps(60);
+
+    siv.runner(backend).run();
+}
@@ -0,0 +1,104 @@
+//! Watching a game streamed with `--stream`.
+
+use cursive::traits::*;
+use cursive::views::{Canvas, LinearLayout};
+use cursive::Cursive;
+use snake::{GameStatus, Location, StreamReader};
+
+use std::io::{BufReader, Read};
+use std::thread;
+
+use crate::{fail, glyph, start_terminal};
+
+/// What the viewer shows of a game stream
+struct Watched {
+    frame: Vec<&'static str>,
+    status: String,
+}
+
+/// Plays back a stream written with `--stream`, from a file or `-` for stdin, at the pace it
+/// was written
+pub fn watch(path: &str) {
+    let input: Box<dyn Read + Send> = match path {
+        "-" => Box::new(std::io::stdin()),
+        path => Box::new(
+            std::fs::File::open(path).unwrap_or_else(|e| fail(format_args!("{}: {}", path, e))),
+        ),
+    };
+    let mut stream = StreamReader::new(BufReader::new(input))
+        .unwrap_or_else(|e| fail(format_args!("{}: {}", path, e)));
+    let (width, height) = (stream.board().width(), stream.board().height());
+
+    let watched = std::sync::Arc::new(std::sync::Mutex::new(Watched {
+        frame: vec![" "; width * height],
+        status: "Waiting for the stream ".into(),
+    }));
+    thread::spawn({
+        let w = watched.clone();
+        move || {
+            let mut started: Option<(std::time::Instant, u64)> = None;
+            loop {
+                let frame = match stream.next_frame() {
+                    Ok(Some(frame)) => frame,
+                    Ok(None) => {
+                        w.lock().unwrap().status.insert_str(0, "End of stream ");
+                        return;
+                    }
+                    Err(e) => {
+                        w.lock().unwrap().status = format!("Broken stream: {} ", e);
+                        return;
+                    }
+                };
+
+                // Frames that come in late, like from a live game, are shown right away
+                let (clock, first) =
+                    *started.get_or_insert((std::time::Instant::now(), frame.millis));
+                let due =
+                    clock + std::time::Duration::from_millis(frame.millis.saturating_sub(first));
+                thread::sleep(due.saturating_duration_since(std::time::Instant::now()));
+
+                let mut watched = w.lock().unwrap();
+                for (Location { x, y }, square) in frame.changes {
+                    watched.frame[y as usize * width + x as usize] = glyph(square);
+                }
+                let status = match frame.status {
+                    GameStatus::InProgress => "",
+                    GameStatus::Paused => "Paused ",
+                    GameStatus::Lost => "Lost ",
+                    GameStatus::Won => "Won ",
+                };
+                watched.status = format!("{}Tick {} Score {}", status, frame.tick, frame.score);
+            }
+        }
+    });
+
+    let mut siv = Cursive::default();
+    siv.add_global_callback('q', |s| s.quit());
+    siv.add_layer(
+        LinearLayout::vertical()
+            .child(
+                Canvas::new(())
+                    .with_draw({
+                        let w = watched.clone();
+                        move |_, p| {
+                            for (i, s) in w.lock().unwrap().frame.iter().enumerate() {
+                                p.print((i % width, i / width), s);
+                            }
+                        }
+                    })
+                    .fixed_size((width, height)),
+            )
+            .child(
+                Canvas::new(())
+                    .with_draw({
+                        let w = watched.clone();
+                        move |_, p| p.print((0, 0), &w.lock().unwrap().status)
+                    })
+                    .fixed_size((width, 1)),
+            ),
+    );
+
+    siv.set_fps(60);
+
+    siv.runner(start_terminal()).run();
+}